@@ -0,0 +1,293 @@
+//! Command-line tool for AoC leaderboard data
+//!
+//! Data is read from a snapshot file, or downloaded from the API with a session cookie.
+use aoc_data::locale::Locale;
+use aoc_data::render::{Format, Render, Table};
+use aoc_data::score::Scoring;
+use aoc_data::time::{Day, TimeDisplay};
+use aoc_data::{get_aoc_data, get_local_data, AocData, AocError};
+use clap::{Args, Parser, Subcommand};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+/// Formats written by `export`, with their file extensions
+const EXPORT_FORMATS: [(Format, &str); 5] = [
+    (Format::Text, "txt"),
+    (Format::Markdown, "md"),
+    (Format::Html, "html"),
+    (Format::Csv, "csv"),
+    (Format::Json, "json"),
+];
+
+#[derive(Debug, Parser)]
+#[command(name = "aoc", about = "Advent of Code leaderboard data")]
+struct Cli {
+    #[command(subcommand)]
+    cmd: Cmd,
+}
+
+#[derive(Debug, Subcommand)]
+enum Cmd {
+    /// Download a snapshot from the API and save it
+    Fetch {
+        #[arg(long, env = "AOC_COOKIE", hide_env_values = true)]
+        cookie: String,
+        /// Snapshot file to write
+        #[arg(short, long, default_value = "latest.json")]
+        out: PathBuf,
+    },
+    /// Show the standings
+    Scores {
+        #[command(flatten)]
+        source: Source,
+        #[command(flatten)]
+        scoring: ScoringArgs,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Show the new stars between two snapshots
+    Diff {
+        #[command(flatten)]
+        snapshots: Snapshots,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Show the standings of a single day, or only its new stars since `--since`
+    Day {
+        /// Day of the puzzle, 1 to 25
+        day: u32,
+        #[command(flatten)]
+        source: Source,
+        /// Previous snapshot file, to only show the stars acquired since it
+        #[arg(long)]
+        since: Option<PathBuf>,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Show the solve time statistics of each player
+    Stats {
+        #[command(flatten)]
+        source: Source,
+        #[command(flatten)]
+        output: Output,
+    },
+    /// Write the standings and the player statistics in all formats, or only in `--format`
+    Export {
+        #[command(flatten)]
+        source: Source,
+        #[command(flatten)]
+        scoring: ScoringArgs,
+        /// Only export in this format
+        #[arg(long, value_parser = parse_enum::<Format>)]
+        format: Option<Format>,
+        #[arg(long, value_parser = parse_enum::<Locale>, default_value = "en")]
+        locale: Locale,
+        /// Directory to write the files to
+        #[arg(short, long, default_value = ".")]
+        out_dir: PathBuf,
+    },
+}
+
+/// Snapshot file, or the live API if no file is given
+#[derive(Debug, Args)]
+struct Source {
+    /// Snapshot file
+    #[arg(short, long)]
+    file: Option<PathBuf>,
+    /// Session cookie, for the live API
+    #[arg(long, env = "AOC_COOKIE", hide_env_values = true)]
+    cookie: Option<String>,
+}
+
+impl Source {
+    async fn load(&self) -> Result<AocData, AocError> {
+        match (&self.file, &self.cookie) {
+            (Some(file), _) => get_local_data(&file.to_string_lossy()),
+            (None, Some(cookie)) => get_aoc_data(cookie).await,
+            (None, None) => Err(AocError::Param {
+                param: "source".to_string(),
+                val: String::new(),
+                reason: "Give a snapshot file or a session cookie".to_string(),
+            }),
+        }
+    }
+}
+
+/// Previous and latest snapshot files
+#[derive(Debug, Args)]
+struct Snapshots {
+    prev: PathBuf,
+    latest: PathBuf,
+}
+
+impl Snapshots {
+    fn load(&self) -> Result<(AocData, AocData), AocError> {
+        Ok((
+            get_local_data(&self.prev.to_string_lossy())?,
+            get_local_data(&self.latest.to_string_lossy())?,
+        ))
+    }
+}
+
+#[derive(Debug, Args)]
+struct ScoringArgs {
+    /// Scoring strategy: local_score, stars, time_since_unlock or part_2_delta
+    #[arg(long, default_value = "local_score")]
+    scoring: String,
+    /// Time cap of the time based scoring strategies
+    #[arg(long, default_value_t = 24)]
+    cap_hours: u32,
+}
+
+impl ScoringArgs {
+    fn scoring(&self) -> Result<Scoring, AocError> {
+        serde_json::from_value(json!({"kind": self.scoring, "cap_hours": self.cap_hours})).map_err(
+            |err| AocError::Param {
+                param: "scoring".to_string(),
+                val: self.scoring.clone(),
+                reason: err.to_string(),
+            },
+        )
+    }
+}
+
+#[derive(Debug, Args)]
+struct Output {
+    /// Output format: text, markdown, html, csv or json
+    #[arg(long, value_parser = parse_enum::<Format>, default_value = "text")]
+    format: Format,
+    #[arg(long, value_parser = parse_enum::<Locale>, default_value = "en")]
+    locale: Locale,
+    /// IANA time zone of the timestamps, the local time zone if not given
+    #[arg(long)]
+    time_zone: Option<chrono_tz::Tz>,
+}
+
+impl Output {
+    fn print(&self, table: &Table) {
+        print!("{}", self.format.render(table));
+    }
+
+    fn time_display(&self) -> TimeDisplay {
+        TimeDisplay {
+            time_zone: self.time_zone,
+            ..TimeDisplay::default()
+        }
+    }
+}
+
+/// Parse a value by its serialized name, e.g. "markdown" for [`Format::Markdown`]
+fn parse_enum<T: DeserializeOwned>(name: &str) -> Result<T, String> {
+    serde_json::from_value(Value::String(name.to_string())).map_err(|err| err.to_string())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    if let Err(err) = run(Cli::parse().cmd).await {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cmd: Cmd) -> Result<(), AocError> {
+    match cmd {
+        Cmd::Fetch { cookie, out } => {
+            get_aoc_data(&cookie)
+                .await?
+                .write_to_file(&out.to_string_lossy())?;
+        }
+        Cmd::Scores {
+            source,
+            scoring,
+            output,
+        } => {
+            let data = source.load().await?;
+            output.print(&data.scores_table(&scoring.scoring()?, output.locale));
+        }
+        Cmd::Diff { snapshots, output } => {
+            let (prev, latest) = snapshots.load()?;
+            match latest.diff(&prev) {
+                Some(diff) => output.print(&diff.table(output.locale, output.time_display())),
+                None => eprintln!("No news"),
+            }
+        }
+        Cmd::Day {
+            day,
+            source,
+            since,
+            output,
+        } => {
+            let day = Day::try_new(day)?;
+            let latest = source.load().await?;
+            let (locale, times) = (output.locale, output.time_display());
+            match since {
+                Some(prev) => match latest.diff(&get_local_data(&prev.to_string_lossy())?) {
+                    Some(diff) => output.print(&diff.day_table(day, locale, times)),
+                    None => eprintln!("No news"),
+                },
+                None => output.print(&latest.day_table(day, locale, times)),
+            }
+        }
+        Cmd::Stats { source, output } => {
+            let data = source.load().await?;
+            output.print(&data.stats_table(output.locale)?);
+        }
+        Cmd::Export {
+            source,
+            scoring,
+            format,
+            locale,
+            out_dir,
+        } => {
+            let data = source.load().await?;
+            let tables = [
+                ("standings", data.scores_table(&scoring.scoring()?, locale)),
+                ("stats", data.stats_table(locale)?),
+            ];
+            fs::create_dir_all(&out_dir)?;
+            let formats = EXPORT_FORMATS
+                .iter()
+                .filter(|(fmt, _)| format.map_or(true, |only| only == *fmt));
+            for (fmt, extension) in formats {
+                for (name, table) in tables.iter() {
+                    let file = out_dir.join(format!("{}.{}", name, extension));
+                    fs::write(&file, fmt.render(table))?;
+                    println!("Wrote {}", file.display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parse_scoring() {
+        let scoring = |kind: &str| {
+            ScoringArgs {
+                scoring: kind.to_string(),
+                cap_hours: 12,
+            }
+            .scoring()
+            .ok()
+        };
+        assert_eq!(scoring("stars"), Some(Scoring::Stars));
+        assert_eq!(
+            scoring("part_2_delta"),
+            Some(Scoring::Part2Delta { cap_hours: 12 })
+        );
+        assert_eq!(scoring("fastest"), None);
+        assert_eq!(parse_enum::<Format>("csv"), Ok(Format::Csv));
+    }
+}
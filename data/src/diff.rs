@@ -1,34 +1,109 @@
-use crate::time::{Day, TimeStamp};
-use crate::{Player, STAR_SYMBOL};
-use std::collections::{BTreeMap, HashMap};
+use crate::locale::{fill, Locale};
+use crate::render::{Render, Table};
+use crate::time::{Day, TimeDisplay, TimeStamp};
+use crate::{Part, Player, EARNED_STAR_SYMBOL, STAR_SYMBOL};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Clone, Debug)]
 pub struct Diff {
     pub(crate) new_players: Vec<Player>,
     pub(crate) removed_players: Vec<Player>,
     pub(crate) new_stars: HashMap<String, BTreeMap<Day, NewStars>>,
+    pub(crate) rank_changes: Vec<RankChange>,
+    pub(crate) overtakes: Vec<Overtake>,
 }
 
 impl Diff {
-    pub fn fmt(self) -> String {
-        let mut fmt_diff = String::new();
-        for (pl, stars) in self.new_stars.iter() {
+    /// Render the new stars, rank changes and new players with `render`
+    pub fn fmt(&self, render: &dyn Render, locale: Locale, times: TimeDisplay) -> String {
+        self.tables(locale, times)
+            .iter()
+            .map(|table| render.render(table))
+            .join("\n")
+    }
+
+    /// New stars, rank changes and new players as separate tables, without the empty ones
+    pub fn tables(&self, locale: Locale, times: TimeDisplay) -> Vec<Table> {
+        vec![
+            self.table(locale, times),
+            self.ranks_table(locale),
+            self.new_players_table(locale),
+        ]
+        .into_iter()
+        .filter(|table| !table.rows.is_empty())
+        .collect()
+    }
+
+    /// New stars of all days as a table, by player and day
+    pub fn table(&self, locale: Locale, times: TimeDisplay) -> Table {
+        let cat = locale.catalog();
+        let header = [cat.player, cat.day, cat.stars, cat.times, cat.placing];
+        let mut table = Table::new(cat.new_stars, &header);
+        for (pl, stars) in self.new_stars.iter().sorted_by_key(|(pl, _)| *pl) {
             for (day, new_stars) in stars {
-                fmt_diff.push_str(&format!(
-                    "{0: <20} - Dag {1: <2}: {2:<2}\n",
-                    pl,
-                    day,
-                    new_stars.fmt()
-                ));
+                let mut row = vec![pl.clone(), day.to_string()];
+                row.extend(star_cells(day, new_stars, locale, times));
+                table.push(row);
             }
         }
-        if !self.new_players.is_empty() {
-            fmt_diff.push_str("New players: ");
-            for pl in &self.new_players {
-                fmt_diff.push_str(&format!("{} ", pl.name));
+        table
+    }
+
+    /// New stars for a single day as a table, by player
+    pub fn day_table(&self, day: Day, locale: Locale, times: TimeDisplay) -> Table {
+        let cat = locale.catalog();
+        let title = format!("{} {}", cat.day, day);
+        let header = [cat.player, cat.stars, cat.times, cat.placing];
+        let mut table = Table::new(&title, &header);
+        for (pl, stars) in self.new_stars.iter().sorted_by_key(|(pl, _)| *pl) {
+            if let Some(new_stars) = stars.get(&day) {
+                let mut row = vec![pl.clone()];
+                row.extend(star_cells(&day, new_stars, locale, times));
+                table.push(row);
             }
         }
-        fmt_diff
+        table
+    }
+
+    /// Rank changes as a table, with the players each player overtook
+    pub fn ranks_table(&self, locale: Locale) -> Table {
+        let cat = locale.catalog();
+        let header = [cat.player, cat.rank, cat.previous, cat.overtook];
+        let mut table = Table::new(cat.rank_changes, &header);
+        for change in &self.rank_changes {
+            let overtook = self
+                .overtakes
+                .iter()
+                .find(|overtake| overtake.name == change.name)
+                .map(|overtake| locale.list(&overtake.overtaken))
+                .unwrap_or_default();
+            table.push(vec![
+                change.name.clone(),
+                format!("{}{}", change.arrow(), change.new),
+                change.prev.to_string(),
+                overtook,
+            ]);
+        }
+        table
+    }
+
+    /// New players as a table, with their stars
+    pub fn new_players_table(&self, locale: Locale) -> Table {
+        let cat = locale.catalog();
+        let mut table = Table::new(cat.new_players, &[cat.player, cat.stars]);
+        for pl in &self.new_players {
+            table.push(vec![pl.name.clone(), pl.stars.to_string()]);
+        }
+        table
+    }
+
+    /// Days with at least one new star
+    pub fn days(&self) -> BTreeSet<Day> {
+        self.new_stars
+            .values()
+            .flat_map(|stars| stars.keys().copied())
+            .collect()
     }
 
     pub fn new_players(&self) -> impl Iterator<Item = &Player> {
@@ -42,32 +117,213 @@ impl Diff {
     pub fn new_stars(&self) -> impl Iterator<Item = (&String, &BTreeMap<Day, NewStars>)> {
         self.new_stars.iter()
     }
+
+    /// Players that changed position in the standings, ordered by their new rank.
+    pub fn rank_changes(&self) -> impl Iterator<Item = &RankChange> {
+        self.rank_changes.iter()
+    }
+
+    pub fn overtakes(&self) -> impl Iterator<Item = &Overtake> {
+        self.overtakes.iter()
+    }
+}
+
+/// Change of a player's position in the standings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RankChange {
+    pub name: String,
+    pub prev: usize,
+    pub new: usize,
+}
+
+impl RankChange {
+    /// Up arrow for a better rank, down arrow for a worse one
+    pub fn arrow(&self) -> char {
+        if self.new < self.prev {
+            '\u{2191}'
+        } else {
+            '\u{2193}'
+        }
+    }
+}
+
+impl std::fmt::Display for RankChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}{} ({})",
+            self.name,
+            self.arrow(),
+            self.new,
+            self.prev
+        )
+    }
+}
+
+/// A player passing other players in the standings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Overtake {
+    pub name: String,
+    /// The overtaken players, by their new rank
+    pub overtaken: Vec<String>,
+    /// The new rank of the overtaking player
+    pub rank: usize,
+}
+
+impl Overtake {
+    pub fn fmt(&self, locale: Locale) -> String {
+        fill(
+            locale.catalog().overtake,
+            &[
+                &self.name,
+                &locale.list(&self.overtaken),
+                &locale.ordinal(self.rank),
+            ],
+        )
+    }
+}
+
+const MEDALS: [char; 3] = ['\u{1F947}', '\u{1F948}', '\u{1F949}'];
+
+/// Cells with the star symbols, times and best podium finish of new stars
+pub(crate) fn star_cells(
+    day: &Day,
+    new_stars: &NewStars,
+    locale: Locale,
+    times: TimeDisplay,
+) -> Vec<String> {
+    vec![
+        new_stars.fmt_symbols(),
+        new_stars.fmt_times(day, locale, times),
+        new_stars.fmt_podium(day, locale),
+    ]
+}
+
+/// Whether a star was acquired since the previous data point
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum StarStatus {
+    /// Acquired before the previous data point, only included for context.
+    Known,
+    /// Newly acquired
+    New,
+}
+
+/// A star and the position the player finished it in, among all players on the leaderboard
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Star {
+    pub part: Part,
+    pub ts: TimeStamp,
+    /// Players with identical timestamps share the same position.
+    pub position: usize,
+    pub status: StarStatus,
+}
+
+impl Star {
+    pub fn new(part: Part, ts: TimeStamp, position: usize, status: StarStatus) -> Self {
+        Star {
+            part,
+            ts,
+            position,
+            status,
+        }
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.status == StarStatus::New
+    }
+
+    /// Top three finish
+    pub fn is_podium(&self) -> bool {
+        (1..=3).contains(&self.position)
+    }
 }
 
+/// Stars for a day with at least one newly acquired star, ordered by part
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
-pub struct NewStars(pub(crate) Vec<TimeStamp>);
+pub struct NewStars(pub(crate) Vec<Star>);
 
 impl NewStars {
     /// Format new stars for update
     ///
-    /// 1 or 2 star emojis, with corresponding timestamp in hour and minue resolution
-    fn fmt(&self) -> String {
-        let mut str_ = String::new();
-        str_.push_str(&STAR_SYMBOL.to_string().repeat(self.0.len()));
-        let times = if self.0.len() == 1 {
-            format!(" ({})", self.0[0])
-        } else {
-            format!(" ({}, {})", self.0[0], self.0[1])
-        };
-        str_.push_str(&times);
-        str_
+    /// A star emoji for each new star and a plain star for each previously earned star,
+    /// with the timestamps of the new stars as given by `times`.
+    pub(crate) fn fmt(&self, day: &Day, locale: Locale, times: TimeDisplay) -> String {
+        format!(
+            "{} ({})",
+            self.fmt_symbols(),
+            self.fmt_times(day, locale, times)
+        )
+    }
+
+    /// A star emoji for each new star and a plain star for each previously earned star
+    fn fmt_symbols(&self) -> String {
+        self.0
+            .iter()
+            .map(|star| {
+                if star.is_new() {
+                    STAR_SYMBOL
+                } else {
+                    EARNED_STAR_SYMBOL
+                }
+            })
+            .collect()
+    }
+
+    /// Timestamps of the new stars for `day`
+    fn fmt_times(&self, day: &Day, locale: Locale, times: TimeDisplay) -> String {
+        let fmt_times: Vec<String> = self
+            .new_stars()
+            .map(|star| times.fmt(star.ts, *day, locale))
+            .collect();
+        fmt_times.join(", ")
+    }
+
+    /// Format the best podium finish among the new stars, empty if there is none
+    ///
+    /// Finishing part 2 is what counts, so part 1 is only mentioned if part 2 is not on the
+    /// podium.
+    fn fmt_podium(&self, day: &Day, locale: Locale) -> String {
+        let cat = locale.catalog();
+        self.new_stars()
+            .filter(|star| star.is_podium())
+            .last()
+            .map(|star| {
+                let (medal, place) = (MEDALS[star.position - 1], cat.podium[star.position - 1]);
+                let template = match star.part {
+                    Part::One => cat.podium_part_1,
+                    Part::Two => cat.podium_finish,
+                };
+                fill(template, &[&medal, &place, day])
+            })
+            .unwrap_or_default()
     }
 
-    pub fn new(tss: Vec<TimeStamp>) -> Self {
-        NewStars(tss)
+    pub fn new(stars: Vec<Star>) -> Self {
+        NewStars(stars)
     }
 
+    /// Number of stars, both known and new
     pub fn count(&self) -> usize {
         self.0.len()
     }
+
+    /// Number of newly acquired stars
+    pub fn new_count(&self) -> usize {
+        self.new_stars().count()
+    }
+
+    /// All stars, both known and new
+    pub fn stars(&self) -> impl Iterator<Item = &Star> {
+        self.0.iter()
+    }
+
+    /// Newly acquired stars
+    pub fn new_stars(&self) -> impl Iterator<Item = &Star> {
+        self.0.iter().filter(|star| star.is_new())
+    }
+
+    /// Time of the most recent of the new stars
+    pub fn last(&self) -> Option<TimeStamp> {
+        self.new_stars().map(|star| star.ts).max()
+    }
 }
@@ -0,0 +1,151 @@
+//! Daily summary of the leaderboard
+use crate::diff::{NewStars, Overtake, RankChange};
+use crate::locale::{fill, Locale};
+use crate::score::{Exclusion, Score, ScoringStrategy};
+use crate::stats::{fmt_hardest, DayStats};
+use crate::time::{Day, TimeDisplay};
+use crate::{fmt_excluded, fmt_scores, AocData};
+
+/// Number of hardest days in the end of event recap
+const RECAP_HARDEST_DAYS: usize = 5;
+
+/// Summary of a day on the leaderboard
+///
+/// Built from a diff between the latest data and a snapshot from the previous digest.
+#[derive(Clone, Debug)]
+pub struct Digest {
+    day: Day,
+    /// Players with new stars for `day`, in the order they finished.
+    solvers: Vec<(String, NewStars)>,
+    /// Shortest time from part 1 to part 2 for `day`
+    fastest_part_2: Option<(String, chrono::Duration)>,
+    rank_changes: Vec<RankChange>,
+    overtakes: Vec<Overtake>,
+    top: Vec<(String, Score)>,
+    /// Label of the points in `top`
+    label: &'static str,
+    excluded: Vec<Exclusion>,
+    /// Statistics of all days, hardest first, only for the digest of the last day
+    hardest: Vec<DayStats>,
+}
+
+impl Digest {
+    /// Summarise the changes from `prev` to `latest` for the puzzle of `day`
+    ///
+    /// Rank changes and standings are according to `strategy`,
+    /// with the standings limited to the `top_n` best players.
+    /// The digest of the last day also recaps the event with its hardest days.
+    pub fn new(
+        latest: &AocData,
+        prev: &AocData,
+        day: Day,
+        top_n: usize,
+        strategy: &dyn ScoringStrategy,
+    ) -> Self {
+        let diff = latest.diff_with(prev, strategy);
+        let mut solvers: Vec<(String, NewStars)> = diff
+            .as_ref()
+            .map(|diff| {
+                diff.new_stars()
+                    .filter_map(|(name, stars)| {
+                        stars.get(&day).map(|stars| (name.clone(), stars.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        solvers.sort_by_key(|(_, stars)| stars.last());
+
+        let fastest_part_2 = latest
+            .players()
+            .filter_map(|(_, pl)| {
+                let dc = pl.completion_day_level.get(&day)?;
+                let star_2 = dc.star_2?;
+                Some((pl.name.clone(), star_2.ts.duration_since(dc.star_1.ts)))
+            })
+            .min_by_key(|(_, delta)| *delta);
+
+        let (rank_changes, overtakes) = diff
+            .map(|diff| (diff.rank_changes, diff.overtakes))
+            .unwrap_or_default();
+
+        let hardest = if day == Day::last() {
+            latest.day_stats().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        Digest {
+            day,
+            solvers,
+            fastest_part_2,
+            rank_changes,
+            overtakes,
+            top: latest.scores(strategy).into_iter().take(top_n).collect(),
+            label: strategy.label(),
+            excluded: strategy.excluded(latest),
+            hardest,
+        }
+    }
+
+    pub fn fmt(&self, locale: Locale, times: TimeDisplay) -> String {
+        let cat = locale.catalog();
+        let mut fmt_digest = format!("{}\n", fill(cat.digest, &[&self.day]));
+        if !self.solvers.is_empty() {
+            fmt_digest.push_str(&format!("{}:\n", cat.solvers));
+            for (pos, (pl, stars)) in self.solvers.iter().enumerate() {
+                fmt_digest.push_str(&format!(
+                    "{0: <3} {1: <20} {2}\n",
+                    pos + 1,
+                    pl,
+                    stars.fmt(&self.day, locale, times)
+                ));
+            }
+        }
+        if let Some((pl, delta)) = &self.fastest_part_2 {
+            fmt_digest.push_str(&format!(
+                "{}: {} ({})\n",
+                cat.fastest_part_2,
+                pl,
+                locale.duration(*delta)
+            ));
+        }
+        for overtake in &self.overtakes {
+            fmt_digest.push_str(&format!("{}\n", overtake.fmt(locale)));
+        }
+        if !self.rank_changes.is_empty() {
+            fmt_digest.push_str(&format!("{}:\n", cat.rank_changes));
+            for change in &self.rank_changes {
+                fmt_digest.push_str(&format!("{}\n", change));
+            }
+        }
+        fmt_digest.push_str(&format!("{}:\n", fill(cat.top, &[&self.top.len()])));
+        fmt_digest.push_str(&fmt_scores(&self.top, self.label, locale));
+        fmt_digest.push_str(&fmt_excluded(&self.excluded, locale));
+        if !self.hardest.is_empty() {
+            fmt_digest.push_str(&format!("\n{}\n", cat.recap));
+            fmt_digest.push_str(&fmt_hardest(&self.hardest, RECAP_HARDEST_DAYS, locale));
+        }
+        fmt_digest
+    }
+
+    pub fn solvers(&self) -> impl Iterator<Item = &(String, NewStars)> {
+        self.solvers.iter()
+    }
+
+    pub fn fastest_part_2(&self) -> Option<&(String, chrono::Duration)> {
+        self.fastest_part_2.as_ref()
+    }
+
+    pub fn rank_changes(&self) -> impl Iterator<Item = &RankChange> {
+        self.rank_changes.iter()
+    }
+
+    pub fn overtakes(&self) -> impl Iterator<Item = &Overtake> {
+        self.overtakes.iter()
+    }
+
+    /// Statistics of all days for the end of event recap, hardest first
+    pub fn hardest(&self) -> impl Iterator<Item = &DayStats> {
+        self.hardest.iter()
+    }
+}
@@ -2,12 +2,28 @@
 //!
 //! Provides a strictly typed data schema and logic for the [Advent of Code](https://adventofcode.com/) competition API.
 pub mod diff;
+pub mod digest;
+pub mod locale;
+pub mod progression;
+pub mod render;
 pub mod score;
+pub mod stats;
+pub mod text;
 pub mod time;
-use crate::diff::{Diff, NewStars};
-use crate::score::{GlobalScore, LocalScore, Score, StarCount};
-use crate::time::{de_opt_timestamp, de_timestamp, sort_optional_ts, Day, TimeStamp};
+pub mod versus;
+use crate::diff::{star_cells, Diff, NewStars, Overtake, RankChange, Star, StarStatus};
+use crate::locale::Locale;
+use crate::render::{Render, Table};
+use crate::score::{
+    Excluding, Exclusion, Exclusions, GlobalScore, LocalScore, OfficialScore, Points, Score,
+    ScoreMismatch, ScoreReport, ScoringStrategy, SpecialCase, StarCount,
+};
+use crate::time::{
+    check_year, de_opt_timestamp, de_timestamp, sort_optional_ts, Day, TimeDisplay, TimeStamp,
+};
+use derive_more::Display;
 use itertools::Itertools;
+use num::Zero;
 use reqwest::header::COOKIE;
 use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
@@ -22,6 +38,10 @@ use thiserror::Error;
 
 /// For nice formatting
 pub const STAR_SYMBOL: char = '\u{2B50}';
+/// Plain star, for stars that are not news
+pub const EARNED_STAR_SYMBOL: char = '\u{2605}';
+/// Hollow star, for days with only part 1
+pub const PART_1_SYMBOL: char = '\u{2606}';
 
 const API_URL: &'static str = "https://adventofcode.com/2023/leaderboard/private/view/152507.json";
 
@@ -57,31 +77,120 @@ pub struct Player {
 }
 
 impl AocData {
-    pub fn scores_fmt(&self) -> String {
-        let mut fmt_score = String::new();
-        for (pos, (pl, score)) in self.scores().iter().enumerate() {
-            fmt_score.push_str(&format!(
-                "{0: <3} {1: <20} {2:<1}: {3:<5} ls: {4:<4}\n",
-                pos + 1,
-                pl,
-                STAR_SYMBOL,
-                score.stars,
-                score.local
-            ));
+    /// Render standings according to `strategy` with `render`
+    pub fn scores_fmt(
+        &self,
+        strategy: &dyn ScoringStrategy,
+        locale: Locale,
+        render: &dyn Render,
+    ) -> String {
+        render.render(&self.scores_table(strategy, locale))
+    }
+
+    /// Standings according to `strategy` as a table
+    ///
+    /// Days or parts excluded from scoring are listed in the title.
+    pub fn scores_table(&self, strategy: &dyn ScoringStrategy, locale: Locale) -> Table {
+        let cat = locale.catalog();
+        let header = ["#", cat.player, cat.stars, locale.label(strategy.label())];
+        let title = match fmt_excluded(&strategy.excluded(self), locale).trim_end() {
+            "" => cat.standings.to_string(),
+            excluded => format!("{} ({})", cat.standings, excluded),
+        };
+        let mut table = Table::new(&title, &header);
+        for (pos, (pl, score)) in self.scores(strategy).iter().enumerate() {
+            table.push(vec![
+                (pos + 1).to_string(),
+                pl.clone(),
+                score.stars.to_string(),
+                locale.number(score.points.0),
+            ]);
         }
-        fmt_score
+        table
     }
 
-    pub fn scores(&self) -> Vec<(String, Score)> {
-        let mut scores: Vec<(String, Score)> = self
+    /// Standings of `day` as a table, with every player who has a star for the day
+    ///
+    /// Players who finished both parts come first, ordered by their time of part 2, then
+    /// players with only part 1, ordered by their time of part 1.
+    pub fn day_table(&self, day: Day, locale: Locale, times: TimeDisplay) -> Table {
+        let cat = locale.catalog();
+        let title = format!("{} {}", cat.day, day);
+        let header = ["#", cat.player, cat.stars, cat.times, cat.placing];
+        let finish_order = self.finish_order();
+        let mut solvers: Vec<(&str, NewStars)> = self
             .players
-            .iter()
-            .map(|(_, pl)| (pl.name.clone(), pl.score()))
+            .values()
+            .filter_map(|pl| {
+                let stars = pl
+                    .completion_day_level
+                    .get(&day)?
+                    .stars()
+                    .map(|(part, ts)| {
+                        let position = finish_order.position(day, part, ts);
+                        Star::new(part, ts, position, StarStatus::New)
+                    })
+                    .collect();
+                Some((pl.name.as_str(), NewStars::new(stars)))
+            })
+            .collect();
+        solvers.sort_by_key(|(name, stars)| {
+            let last = stars.stars().last().map(|star| star.ts);
+            (Reverse(stars.count()), last, *name)
+        });
+        let mut table = Table::new(&title, &header);
+        for (pos, (name, stars)) in solvers.iter().enumerate() {
+            let mut row = vec![(pos + 1).to_string(), name.to_string()];
+            row.extend(star_cells(&day, stars, locale, times));
+            table.push(row);
+        }
+        table
+    }
+
+    /// Standings according to `strategy`, best first
+    pub fn scores(&self, strategy: &dyn ScoringStrategy) -> Vec<(String, Score)> {
+        let mut scores: Vec<(String, Score)> = self
+            .player_scores(strategy)
+            .into_iter()
+            .map(|(id, score)| (self.players[&id].name.clone(), score))
             .collect();
         scores.sort_unstable_by_key(|(_pl, score)| Reverse(*score));
         scores
     }
 
+    /// Score of each player according to `strategy`
+    pub fn player_scores(&self, strategy: &dyn ScoringStrategy) -> HashMap<PlayerId, Score> {
+        let mut points = strategy.points(self);
+        self.players
+            .iter()
+            .map(|(id, pl)| {
+                let score = Score {
+                    stars: pl.stars,
+                    points: points
+                        .remove(id)
+                        .map(|star_points| star_points.into_values().sum())
+                        .unwrap_or_else(Points::zero),
+                };
+                (*id, score)
+            })
+            .collect()
+    }
+
+    /// Position of each player in the standings according to `strategy`
+    ///
+    /// Players with equal scores share the same rank, i.e. the rank is one more than the number
+    /// of players with a strictly better score.
+    pub fn ranks(&self, strategy: &dyn ScoringStrategy) -> HashMap<PlayerId, usize> {
+        let scores = self.player_scores(strategy);
+        scores
+            .iter()
+            .map(|(id, score)| {
+                let better = scores.values().filter(|other| *other > score).count();
+                (*id, better + 1)
+            })
+            .collect()
+    }
+
     pub fn local_scores(&self) -> HashMap<PlayerId, BTreeMap<Day, (LocalScore, LocalScore)>> {
         // Create an iterator over stars with corresponding player_id and timestamp (Option<Timestamp>).
         // Uses the tuple (day, star number) as key for the stars.
@@ -164,6 +273,21 @@ impl AocData {
         player_day_map.into_iter().chain(no_star_players).collect()
     }
 
+    /// Year of the event
+    pub fn year(&self) -> Result<i32, AocError> {
+        let year = self.event.trim().parse().map_err(|_| AocError::Param {
+            param: "event".to_string(),
+            val: self.event.clone(),
+            reason: "Event is not a year".to_string(),
+        })?;
+        check_year(year)
+    }
+
+    /// Id of the private leaderboard, which is the id of its owner
+    pub fn leaderboard_id(&self) -> PlayerId {
+        self.owner_id
+    }
+
     pub fn players(&self) -> impl Iterator<Item = (&PlayerId, &Player)> {
         self.players.iter()
     }
@@ -172,6 +296,11 @@ impl AocData {
         self.players.len()
     }
 
+    /// Total number of stars of all players
+    pub fn num_stars(&self) -> u32 {
+        self.players().map(|(_, pl)| pl.stars.0).sum()
+    }
+
     fn player_ids(&self) -> impl Iterator<Item = &PlayerId> {
         self.players.keys()
     }
@@ -190,6 +319,13 @@ impl AocData {
     /// set of `new_players` which is a subset of `self.players`.
     /// Same goes for `prev.players[id]` since `id` is in a subset of `prev.players`.
     pub fn diff(&self, prev: &AocData) -> Option<Diff> {
+        self.diff_with(prev, &OfficialScore)
+    }
+
+    /// Aggregate possible diff, with rank changes according to `strategy`
+    ///
+    /// See [`AocData::diff`].
+    pub fn diff_with(&self, prev: &AocData, strategy: &dyn ScoringStrategy) -> Option<Diff> {
         if self.latest_star() == prev.latest_star() && self.player_id_set() == prev.player_id_set()
         {
             None
@@ -202,24 +338,77 @@ impl AocData {
             let removed_players = prev
                 .player_id_set()
                 .difference(&self.player_id_set())
-                .map(|id| prev.players.get(id).unwrap().clone())
+                .map(|id| prev.players[*id].clone())
                 .collect();
             let upd_players = self.updated_players(prev, &new_players);
+            let finish_order = self.finish_order();
             let new_stars = upd_players
-                .map(|(new, prev)| (new.name.clone(), new.diff_stars(prev)))
+                .map(|(new, prev)| (new.name.clone(), new.diff_stars(prev, &finish_order)))
+                .filter(|(_, stars)| !stars.is_empty())
                 .collect();
             let new_players = new_players
                 .into_iter()
-                .map(|id| self.players.get(id).unwrap().clone())
+                .map(|id| self.players[id].clone())
                 .collect();
+            let (rank_changes, overtakes) = self.rank_diff(prev, strategy);
             Some(Diff {
                 new_players,
                 removed_players,
                 new_stars,
+                rank_changes,
+                overtakes,
             })
         }
     }
 
+    /// Changes in the standings compared to `prev`
+    ///
+    /// Only players present in both `self` and `prev` are considered, ordered by their new rank
+    /// and name.
+    /// A player overtook the players that were ranked above them in `prev` and are now ranked
+    /// below them, with one [`Overtake`] per overtaking player.
+    fn rank_diff(
+        &self,
+        prev: &AocData,
+        strategy: &dyn ScoringStrategy,
+    ) -> (Vec<RankChange>, Vec<Overtake>) {
+        let new_ranks = self.ranks(strategy);
+        let prev_ranks = prev.ranks(strategy);
+        let ranks: Vec<(&str, usize, usize)> = new_ranks
+            .iter()
+            .filter_map(|(id, new)| {
+                let name = self.players[id].name.as_str();
+                prev_ranks.get(id).map(|prev| (name, *prev, *new))
+            })
+            .sorted_by_key(|(name, _, new)| (*new, *name))
+            .collect();
+        let rank_changes = ranks
+            .iter()
+            .filter(|(_, prev, new)| prev != new)
+            .map(|(name, prev, new)| RankChange {
+                name: name.to_string(),
+                prev: *prev,
+                new: *new,
+            })
+            .collect();
+        let overtakes = ranks
+            .iter()
+            .filter_map(|(name, prev, new)| {
+                let overtaken: Vec<String> = ranks
+                    .iter()
+                    .filter(|(_, other_prev, other_new)| prev > other_prev && new < other_new)
+                    .map(|(other, _, _)| other.to_string())
+                    .collect();
+                (!overtaken.is_empty()).then(|| Overtake {
+                    name: name.to_string(),
+                    overtaken,
+                    rank: *new,
+                })
+            })
+            .collect();
+        (rank_changes, overtakes)
+    }
+
     /// Updated players
     ///
     /// Get an iterator over players that both:
@@ -238,10 +427,135 @@ impl AocData {
             .filter(move |(id, _player)| !new_players.contains(id))
             .filter(move |(id, player)| {
                 let new_ts = player.last_star_ts();
-                let prev_ts = prev.players.get(id).unwrap().last_star_ts();
+                let prev_ts = prev.players[*id].last_star_ts();
                 new_ts != prev_ts
             })
-            .map(move |(id, player)| (player, prev.players.get(id).unwrap()))
+            .map(move |(id, player)| (player, &prev.players[id]))
+    }
+
+    /// Compare the recomputed local scores to the ones reported by the API
+    ///
+    /// The local scores are recomputed with the official scoring, without points for the
+    /// `excluded` days of the event.
+    /// Players without stars, e.g. players who joined later, still count towards the points
+    /// for each star, just like in the official scoring.
+    ///
+    /// A mismatch means that the parsing or the scoring is wrong.
+    /// For each mismatch, the days where the player's score involves a [`SpecialCase`]
+    /// are listed.
+    ///
+    /// The API has no join dates, so a player is taken to have joined later on the days they
+    /// caught up on: days solved more than a day after the unlock and before the player's first
+    /// star within a day of its unlock.
+    pub fn verify_scores(&self, excluded: &Exclusions) -> ScoreReport {
+        let strategy = Excluding {
+            strategy: OfficialScore,
+            excluded: excluded.clone(),
+        };
+        let excluded = strategy.excluded(self);
+        let ties = self.ties();
+        let joined_later = self.joined_later();
+        let scores = self.player_scores(&strategy);
+        let mut mismatches: Vec<ScoreMismatch> =
+            self.players
+                .iter()
+                .filter_map(|(id, pl)| {
+                    let computed = LocalScore(scores.get(id)?.points.0);
+                    if computed == pl.local_score {
+                        return None;
+                    }
+                    let days =
+                        pl.completion_day_level
+                            .iter()
+                            .filter_map(|(day, dc)| {
+                                let mut cases = Vec::new();
+                                if ties.contains(&(*id, *day)) {
+                                    cases.push(SpecialCase::Tie);
+                                }
+                                if joined_later.contains(&(*id, *day)) {
+                                    cases.push(SpecialCase::JoinedLater);
+                                }
+                                if dc.stars().any(|(part, _)| {
+                                    excluded.iter().any(|ex| ex.contains(*day, part))
+                                }) {
+                                    cases.push(SpecialCase::Excluded);
+                                }
+                                Some((*day, cases)).filter(|(_, cases)| !cases.is_empty())
+                            })
+                            .collect();
+                    Some(ScoreMismatch {
+                        name: pl.name.clone(),
+                        expected: pl.local_score,
+                        computed,
+                        days,
+                    })
+                })
+                .collect();
+        mismatches.sort_by(|a, b| a.name.cmp(&b.name));
+        ScoreReport { mismatches }
+    }
+
+    /// Players and days where the player has a star with the same timestamp as another player
+    fn ties(&self) -> HashSet<(PlayerId, Day)> {
+        self.players
+            .iter()
+            .flat_map(|(id, pl)| {
+                pl.completion_day_level.iter().flat_map(move |(day, dc)| {
+                    dc.stars().map(move |(part, ts)| (*id, *day, part, ts))
+                })
+            })
+            .into_group_map_by(|(_, day, part, ts)| (*day, *part, *ts))
+            .into_values()
+            .filter(|stars| stars.len() > 1)
+            .flatten()
+            .map(|(id, day, _, _)| (id, day))
+            .collect()
+    }
+
+    /// Players and days the player caught up on after joining the leaderboard later
+    ///
+    /// Empty if the event is not a year, since the unlock times are unknown.
+    fn joined_later(&self) -> HashSet<(PlayerId, Day)> {
+        let year = match self.year() {
+            Ok(year) => year,
+            Err(_) => return HashSet::new(),
+        };
+        let late = |day: Day, ts: TimeStamp| ts.duration_since_unlock(day, year).num_days() >= 1;
+        self.players
+            .iter()
+            .flat_map(|(id, pl)| {
+                let first_on_time = pl
+                    .completion_day_level
+                    .iter()
+                    .flat_map(|(day, dc)| dc.stars().map(move |(_, ts)| (*day, ts)))
+                    .filter(|(day, ts)| !late(*day, *ts))
+                    .map(|(_, ts)| ts)
+                    .min();
+                pl.completion_day_level
+                    .iter()
+                    .filter(move |(day, dc)| {
+                        dc.stars().all(|(_, ts)| match first_on_time {
+                            Some(first) => late(**day, ts) && ts < first,
+                            None => late(**day, ts),
+                        })
+                    })
+                    .map(move |(day, _)| (*id, *day))
+            })
+            .collect()
+    }
+
+    /// Timestamps of all players for each star, i.e. for each (day, part), earliest first.
+    fn finish_order(&self) -> FinishOrder {
+        let mut finish_order = self
+            .players
+            .values()
+            .flat_map(|pl| pl.completion_day_level.iter())
+            .flat_map(|(day, dc)| dc.stars().map(move |(part, ts)| ((*day, part), ts)))
+            .into_group_map();
+        finish_order
+            .values_mut()
+            .for_each(|tss| tss.sort_unstable());
+        FinishOrder(finish_order)
     }
 
     pub fn latest_star(&self) -> Option<TimeStamp> {
@@ -257,13 +571,27 @@ impl AocData {
 }
 
 impl Player {
-    fn diff_stars(&self, prev: &Player) -> BTreeMap<Day, NewStars> {
+    /// Days with newly acquired stars compared to `prev`
+    ///
+    /// Stars that are only in `prev`, e.g. if a player's progress was reset, are not new and are
+    /// left out, as are days without any stars in `self`.
+    fn diff_stars(&self, prev: &Player, finish_order: &FinishOrder) -> BTreeMap<Day, NewStars> {
         self.completion_day_level
             .iter()
             .fold(BTreeMap::new(), |mut acc, (day, dc)| {
-                let new_stars = dc.diff(prev.completion_day_level.get(day));
-                // `dc.diff` can return 0, which we don't want to record.
-                if new_stars.count() == 1 || new_stars.count() == 2 {
+                let new_stars = dc
+                    .diff(prev.completion_day_level.get(day))
+                    .into_iter()
+                    .map(|(part, ts, status)| Star {
+                        part,
+                        ts,
+                        position: finish_order.position(*day, part, ts),
+                        status,
+                    })
+                    .collect();
+                let new_stars = NewStars(new_stars);
+                // `dc.diff` can return no stars, which we don't want to record.
+                if new_stars.new_count() > 0 {
                     acc.insert(*day, new_stars);
                 }
                 acc
@@ -277,18 +605,96 @@ impl Player {
     fn last_star_ts(&self) -> Option<TimeStamp> {
         self.last_star_ts
     }
+}
+
+/// Format standings, one line per player
+///
+/// The points are labelled with `label`, e.g. "ls" for local score.
+pub(crate) fn fmt_scores(
+    scores: &[(String, Score)],
+    label: &'static str,
+    locale: Locale,
+) -> String {
+    let mut fmt_score = String::new();
+    for (pos, (pl, score)) in scores.iter().enumerate() {
+        fmt_score.push_str(&format!(
+            "{0: <3} {1: <20} {2:<1}: {3:<5} {4}: {5:<4}\n",
+            pos + 1,
+            pl,
+            STAR_SYMBOL,
+            score.stars,
+            locale.label(label),
+            locale.number(score.points.0)
+        ));
+    }
+    fmt_score
+}
 
-    fn score(&self) -> Score {
-        Score {
-            stars: self.stars,
-            local: self.local_score,
-        }
+/// Format days or parts excluded from scoring, empty if there are none.
+pub(crate) fn fmt_excluded(excluded: &[Exclusion], locale: Locale) -> String {
+    if excluded.is_empty() {
+        return String::new();
     }
+    let excluded: Vec<String> = excluded.iter().map(|ex| ex.fmt(locale)).collect();
+    format!("{}: {}\n", locale.catalog().excluded, excluded.join(", "))
 }
 
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Copy, Clone, Debug, Display, Hash, Eq, PartialEq, Deserialize, Serialize)]
 pub struct PlayerId(u32);
 
+/// Puzzle part, each part gives one star
+#[derive(
+    Copy, Clone, Debug, Display, Hash, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize,
+)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Part {
+    #[display(fmt = "1")]
+    One,
+    #[display(fmt = "2")]
+    Two,
+}
+
+impl TryFrom<u8> for Part {
+    type Error = AocError;
+
+    fn try_from(part: u8) -> Result<Self, Self::Error> {
+        match part {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            _ => Err(AocError::Param {
+                param: "Part".to_string(),
+                val: part.to_string(),
+                reason: "Part not 1 or 2".to_string(),
+            }),
+        }
+    }
+}
+
+impl From<Part> for u8 {
+    fn from(part: Part) -> Self {
+        match part {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+/// Sorted timestamps of all players, keyed by (day, part)
+struct FinishOrder(HashMap<(Day, Part), Vec<TimeStamp>>);
+
+impl FinishOrder {
+    /// Finishing position of a star acquired at `ts`
+    ///
+    /// Players with identical timestamps share the same position.
+    fn position(&self, day: Day, part: Part, ts: TimeStamp) -> usize {
+        self.0
+            .get(&(day, part))
+            .map(|tss| tss.partition_point(|other| *other < ts))
+            .unwrap_or(0)
+            + 1
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct DayCompletion {
     #[serde(rename = "1")]
@@ -298,26 +704,35 @@ struct DayCompletion {
 }
 
 impl DayCompletion {
-    fn diff(&self, other: Option<&DayCompletion>) -> NewStars {
-        match other {
-            // If the key exists in prev, the first star must be taken.
-            // Check if the second star is taken in the new data but not in the prev.
-            // If yes, return both timestamps since we want to display two stars, even though there is only
-            // one new star.
-            Some(prev_dc) => {
-                if prev_dc.star_2.is_none() && self.star_2.is_some() {
-                    NewStars(vec![self.star_1.ts, self.star_2.unwrap().ts])
-                } else {
-                    NewStars(vec![])
-                }
-            }
-            // If the key does not exist in prev, then either one or both stars have been
-            // acquired since prev.
-            None => match self.star_2 {
-                None => NewStars(vec![self.star_1.ts]),
-                Some(star_2) => NewStars(vec![self.star_1.ts, star_2.ts]),
-            },
+    /// Acquired stars, ordered by part
+    fn stars(&self) -> impl Iterator<Item = (Part, TimeStamp)> {
+        once((Part::One, self.star_1.ts)).chain(self.star_2.map(|star_2| (Part::Two, star_2.ts)))
+    }
+
+    /// Stars to display as new, ordered by part
+    ///
+    /// If any star is newly acquired compared to `other`, all acquired stars are returned, with
+    /// the ones already present in `other` marked as known.
+    /// If there are no new stars, the result is empty.
+    fn diff(&self, other: Option<&DayCompletion>) -> Vec<(Part, TimeStamp, StarStatus)> {
+        // If the key does not exist in prev, then either one or both stars have been
+        // acquired since prev.
+        let known: Vec<Part> = other
+            .map(|prev_dc| prev_dc.stars().map(|(part, _)| part).collect())
+            .unwrap_or_default();
+        if self.stars().all(|(part, _)| known.contains(&part)) {
+            return vec![];
         }
+        self.stars()
+            .map(|(part, ts)| {
+                let status = if known.contains(&part) {
+                    StarStatus::Known
+                } else {
+                    StarStatus::New
+                };
+                (part, ts, status)
+            })
+            .collect()
     }
 }
 
@@ -374,6 +789,19 @@ pub enum AocError {
     },
 }
 
+impl AocError {
+    /// Short name of the kind of error, e.g. "api" or "serde"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AocError::Param { .. } => "param",
+            AocError::Serde(_) => "serde",
+            AocError::AocApi { .. } => "api",
+            AocError::IO { .. } => "io",
+            AocError::Env { .. } => "env",
+        }
+    }
+}
+
 /// Special parsing of [`PlayerId`]
 ///
 /// The player id in the `owner_id` field is repr. as an int (JSON Number)
@@ -394,6 +822,7 @@ fn de_player_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PlayerId,
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::render::Text;
     use num::Zero;
 
     /// Specific bug in which new data with no new stars but a new player generated an empty
@@ -435,6 +864,127 @@ mod test {
             players,
         };
         assert!(later.diff(&prev).unwrap().new_players().count() == 1);
-        assert!(!later.diff(&prev).unwrap().fmt().is_empty());
+        assert!(!later
+            .diff(&prev)
+            .unwrap()
+            .fmt(&Text, Locale::En, TimeDisplay::default())
+            .is_empty());
+    }
+
+    fn player(name: &str, stars: u32, local_score: u32) -> Player {
+        Player {
+            name: name.to_string(),
+            completion_day_level: BTreeMap::new(),
+            local_score: LocalScore(local_score),
+            global_score: GlobalScore::zero(),
+            stars: StarCount(stars),
+            last_star_ts: Some(TimeStamp::new(u64::from(stars))),
+        }
+    }
+
+    /// Leaderboard of `players`, with ids in order
+    fn data(players: Vec<Player>) -> AocData {
+        AocData {
+            event: "Test".to_string(),
+            owner_id: PlayerId(0),
+            players: (0..).map(PlayerId).zip(players).collect(),
+        }
+    }
+
+    /// Completed days, given as `(day, star_1, star_2)` timestamps
+    fn completion(days: &[(u32, u64, Option<u64>)]) -> BTreeMap<Day, DayCompletion> {
+        days.iter()
+            .map(|(day, star_1, star_2)| {
+                let completion = DayCompletion {
+                    star_1: StarProgress {
+                        ts: TimeStamp::new(*star_1),
+                    },
+                    star_2: star_2.map(|ts| StarProgress {
+                        ts: TimeStamp::new(ts),
+                    }),
+                };
+                (Day(*day), completion)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_overtakes() {
+        let prev = data(vec![
+            player("Aba", 4, 10),
+            player("Bab", 3, 8),
+            player("Cac", 2, 6),
+        ]);
+        let later = data(vec![
+            player("Aba", 4, 10),
+            player("Bab", 3, 8),
+            player("Cac", 5, 12),
+        ]);
+        let diff = later.diff(&prev).unwrap();
+        let changes: Vec<(&str, usize, usize)> = diff
+            .rank_changes()
+            .map(|c| (c.name.as_str(), c.prev, c.new))
+            .collect();
+        assert_eq!(changes, vec![("Cac", 3, 1), ("Aba", 1, 2), ("Bab", 2, 3)]);
+        let overtakes: Vec<&Overtake> = diff.overtakes().collect();
+        assert_eq!(
+            overtakes,
+            vec![&Overtake {
+                name: "Cac".to_string(),
+                overtaken: vec!["Aba".to_string(), "Bab".to_string()],
+                rank: 1,
+            }]
+        );
+        assert_eq!(
+            overtakes[0].fmt(Locale::En),
+            "Cac overtook Aba and Bab for 1st place!"
+        );
+        assert_eq!(
+            diff.ranks_table(Locale::En).rows[0],
+            vec!["Cac", "\u{2191}1", "3", "Aba and Bab"]
+        );
+        assert_eq!(later.num_stars(), 12);
+    }
+
+    #[test]
+    fn test_podium() {
+        let mut aba = player("Aba", 1, 0);
+        aba.completion_day_level = completion(&[(1, 10, None)]);
+        let mut bab = player("Bab", 1, 0);
+        bab.completion_day_level = completion(&[(1, 20, None)]);
+        let prev = data(vec![aba.clone(), bab.clone()]);
+        bab.completion_day_level = completion(&[(1, 20, Some(30))]);
+        bab.last_star_ts = Some(TimeStamp::new(30));
+        let later = data(vec![aba, bab]);
+
+        let diff = later.diff(&prev).unwrap();
+        let (_, new_stars) = diff.new_stars().next().unwrap();
+        let positions: Vec<usize> = new_stars[&Day(1)].stars().map(|s| s.position).collect();
+        assert_eq!(positions, vec![2, 1]);
+        let fmt_diff = diff.fmt(&Text, Locale::En, TimeDisplay::default());
+        assert!(fmt_diff.contains("\u{2605}\u{2B50}"));
+        assert!(fmt_diff.contains("\u{1F947} first to finish Day 1"));
+    }
+
+    #[test]
+    fn test_removed_stars() {
+        let mut aba = player("Aba", 3, 0);
+        aba.completion_day_level = completion(&[(1, 10, Some(20)), (2, 30, None)]);
+        let mut bab = player("Bab", 2, 0);
+        bab.completion_day_level = completion(&[(1, 15, Some(25))]);
+        let prev = data(vec![aba.clone(), bab.clone()]);
+        // Aba lost part 2 of day 1 and day 2 but got day 3, Bab lost everything.
+        aba.completion_day_level = completion(&[(1, 10, None), (3, 40, None)]);
+        aba.last_star_ts = Some(TimeStamp::new(40));
+        bab.completion_day_level = completion(&[]);
+        bab.last_star_ts = None;
+        let later = data(vec![aba, bab]);
+
+        let diff = later.diff(&prev).unwrap();
+        let new_stars: Vec<(&String, Vec<&Day>)> = diff
+            .new_stars()
+            .map(|(pl, stars)| (pl, stars.keys().collect()))
+            .collect();
+        assert_eq!(new_stars, vec![(&"Aba".to_string(), vec![&Day(3)])]);
     }
 }
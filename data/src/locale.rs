@@ -0,0 +1,400 @@
+//! Message catalog and locale dependent formatting of user-facing text
+//!
+//! Messages with parameters are templates, where each `{}` is replaced by the next argument,
+//! see [`fill`].
+use chrono::{NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Language of the user-facing text
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    En,
+    Sv,
+}
+
+/// All user-facing strings for a locale
+pub struct Catalog {
+    pub day: &'static str,
+    pub part: &'static str,
+    /// Template: medal, place, day
+    pub podium_part_1: &'static str,
+    /// Template: medal, place, day
+    pub podium_finish: &'static str,
+    pub podium: [&'static str; 3],
+    /// Template: player, overtaken players, ordinal rank
+    pub overtake: &'static str,
+    /// Last separator of a list, e.g. "A, B and C"
+    pub and: &'static str,
+    pub rank_changes: &'static str,
+    pub new_players: &'static str,
+    pub excluded: &'static str,
+    /// Template: day
+    pub excluded_day: &'static str,
+    /// Template: day, part
+    pub excluded_part: &'static str,
+    /// Template: day
+    pub digest: &'static str,
+    pub recap: &'static str,
+    pub solvers: &'static str,
+    pub fastest_part_2: &'static str,
+    /// Template: number of players
+    pub top: &'static str,
+    /// Template: player
+    pub stats: &'static str,
+    pub player_stats: &'static str,
+    pub part_1: &'static str,
+    pub part_2: &'static str,
+    pub part_1_to_2: &'static str,
+    pub median: &'static str,
+    pub mean: &'static str,
+    pub fastest_day: &'static str,
+    pub slowest_day: &'static str,
+    pub release_days: &'static str,
+    pub longest_streak: &'static str,
+    /// Template: part 2 solvers, part 1 solvers, share
+    pub day_stats: &'static str,
+    pub spread: &'static str,
+    pub hardest: &'static str,
+    pub ties: &'static str,
+    pub tie: &'static str,
+    /// Template: player, margin
+    pub won_by: &'static str,
+    /// Template: player
+    pub unanswered: &'static str,
+    pub local_score_gap: &'static str,
+    pub standings: &'static str,
+    pub player: &'static str,
+    pub stars: &'static str,
+    pub new_stars: &'static str,
+    pub times: &'static str,
+    pub points: &'static str,
+    pub rank: &'static str,
+    pub previous: &'static str,
+    pub overtook: &'static str,
+    /// Header of the best podium finish of new stars
+    pub placing: &'static str,
+    /// Template: day
+    pub unlocked: &'static str,
+    /// Template: name
+    pub no_player: &'static str,
+    pub time_zone_fair: &'static str,
+    /// Template: duration
+    pub after_unlock: &'static str,
+    /// Unit suffixes for days, hours, minutes and seconds
+    pub units: [&'static str; 4],
+    /// Labels of the scoring strategies, keyed by [`crate::score::ScoringStrategy::label`]
+    pub labels: [(&'static str, &'static str); 4],
+}
+
+const EN: Catalog = Catalog {
+    day: "Day",
+    part: "part",
+    podium_part_1: "{} {} to part 1 of Day {}",
+    podium_finish: "{} {} to finish Day {}",
+    podium: ["first", "second", "third"],
+    overtake: "{} overtook {} for {} place!",
+    and: "and",
+    rank_changes: "Rank changes",
+    new_players: "New players",
+    excluded: "Excluded from scoring",
+    excluded_day: "day {}",
+    excluded_part: "day {} part {}",
+    digest: "Daily digest, day {}",
+    recap: "End of event recap",
+    solvers: "Solvers",
+    fastest_part_2: "Fastest part 2",
+    top: "Top {}",
+    stats: "Stats for {}",
+    player_stats: "Player statistics",
+    part_1: "Part 1",
+    part_2: "Part 2",
+    part_1_to_2: "Part 1 to 2",
+    median: "median",
+    mean: "mean",
+    fastest_day: "Fastest day",
+    slowest_day: "Slowest day",
+    release_days: "Done on release day",
+    longest_streak: "Longest streak",
+    day_stats: "{}/{} finished part 2 ({})",
+    spread: "spread",
+    hardest: "Hardest days",
+    ties: "ties",
+    tie: "tie",
+    won_by: "{} by {}",
+    unanswered: "{}, unanswered",
+    local_score_gap: "Local score gap",
+    standings: "Standings",
+    player: "Player",
+    stars: "Stars",
+    new_stars: "New stars",
+    times: "Times",
+    points: "Points",
+    rank: "Rank",
+    previous: "Previous",
+    overtook: "Overtook",
+    placing: "Podium",
+    unlocked: "Day {} is unlocked!",
+    no_player: "No player called {}",
+    time_zone_fair: "Time zone fair",
+    after_unlock: "{} after unlock",
+    units: ["d", "h", "m", "s"],
+    labels: [
+        ("ls", "ls"),
+        ("stars", "stars"),
+        ("pts", "pts"),
+        ("fair", "fair"),
+    ],
+};
+
+const SV: Catalog = Catalog {
+    day: "Dag",
+    part: "del",
+    podium_part_1: "{} {} på del 1 av dag {}",
+    podium_finish: "{} {} att klara dag {}",
+    podium: ["först", "tvåa", "trea"],
+    overtake: "{} gick om {} och tog {} plats!",
+    and: "och",
+    rank_changes: "Nya placeringar",
+    new_players: "Nya spelare",
+    excluded: "Räknas inte",
+    excluded_day: "dag {}",
+    excluded_part: "dag {} del {}",
+    digest: "Dagens sammanfattning, dag {}",
+    recap: "Sammanfattning av eventet",
+    solvers: "Lösare",
+    fastest_part_2: "Snabbast del 2",
+    top: "Topp {}",
+    stats: "Statistik för {}",
+    player_stats: "Spelarstatistik",
+    part_1: "Del 1",
+    part_2: "Del 2",
+    part_1_to_2: "Del 1 till 2",
+    median: "median",
+    mean: "medel",
+    fastest_day: "Snabbaste dag",
+    slowest_day: "Långsammaste dag",
+    release_days: "Klara samma dag",
+    longest_streak: "Längsta svit",
+    day_stats: "{}/{} klarade del 2 ({})",
+    spread: "spridning",
+    hardest: "Svåraste dagar",
+    ties: "oavgjorda",
+    tie: "oavgjort",
+    won_by: "{} med {}",
+    unanswered: "{}, obesvarad",
+    local_score_gap: "Poängskillnad",
+    standings: "Ställning",
+    player: "Spelare",
+    stars: "Stjärnor",
+    new_stars: "Nya stjärnor",
+    times: "Tider",
+    points: "Poäng",
+    rank: "Placering",
+    previous: "Tidigare",
+    overtook: "Gick om",
+    placing: "Pallplats",
+    unlocked: "Dag {} är upplåst!",
+    no_player: "Ingen spelare heter {}",
+    time_zone_fair: "Tidszonsrättvist",
+    after_unlock: "{} efter upplåsning",
+    units: [" d", " tim", " min", " s"],
+    labels: [
+        ("ls", "ls"),
+        ("stars", "stj"),
+        ("pts", "p"),
+        ("fair", "rättvis"),
+    ],
+};
+
+impl Locale {
+    pub fn catalog(self) -> &'static Catalog {
+        match self {
+            Locale::En => &EN,
+            Locale::Sv => &SV,
+        }
+    }
+
+    /// Translated label of a scoring strategy, unknown labels are kept as is.
+    pub fn label(self, label: &'static str) -> &'static str {
+        self.catalog()
+            .labels
+            .iter()
+            .find(|(key, _)| *key == label)
+            .map(|(_, translated)| *translated)
+            .unwrap_or(label)
+    }
+
+    /// Ordinal number, e.g. "2nd" or "2:a"
+    pub fn ordinal(self, n: usize) -> String {
+        let suffix = match self {
+            Locale::En => match (n % 10, n % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            },
+            Locale::Sv => match (n % 10, n % 100) {
+                (_, 11..=12) => ":e",
+                (1, _) | (2, _) => ":a",
+                _ => ":e",
+            },
+        };
+        format!("{}{}", n, suffix)
+    }
+
+    /// Number with thousands separators, e.g. "1,234" or "1 234"
+    pub fn number(self, n: u32) -> String {
+        let separator = match self {
+            Locale::En => ',',
+            Locale::Sv => '\u{00A0}',
+        };
+        let digits = n.to_string();
+        let mut number = String::new();
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx) % 3 == 0 {
+                number.push(separator);
+            }
+            number.push(digit);
+        }
+        number
+    }
+
+    /// List of `items`, e.g. "A, B and C" or "A, B och C"
+    pub fn list(self, items: &[String]) -> String {
+        match items.split_last() {
+            Some((last, rest)) if !rest.is_empty() => {
+                format!("{} {} {}", rest.join(", "), self.catalog().and, last)
+            }
+            Some((last, _)) => last.clone(),
+            None => String::new(),
+        }
+    }
+
+    /// Share as a whole percentage, e.g. "50%" or "50 %"
+    pub fn percent(self, share: f64) -> String {
+        match self {
+            Locale::En => format!("{:.0}%", 100.0 * share),
+            Locale::Sv => format!("{:.0}\u{00A0}%", 100.0 * share),
+        }
+    }
+
+    /// Time of day, e.g. "9:05 PM" or "21:05"
+    pub fn time(self, dt: NaiveDateTime) -> String {
+        let (hour, min) = (dt.hour(), dt.minute());
+        match self {
+            Locale::En => {
+                let suffix = if hour < 12 { "AM" } else { "PM" };
+                let hour = match hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                format!("{}:{:02} {}", hour, min, suffix)
+            }
+            Locale::Sv => format!("{:02}:{:02}", hour, min),
+        }
+    }
+
+    /// Date and time of day, e.g. "2021-12-05 9:05 PM" or "2021-12-05 21:05"
+    pub fn date_time(self, dt: NaiveDateTime) -> String {
+        format!("{} {}", dt.format("%Y-%m-%d"), self.time(dt))
+    }
+
+    /// Duration with the two most significant units, e.g. "3h 12m" or "3 tim 12 min"
+    pub fn duration(self, duration: chrono::Duration) -> String {
+        let [d, h, m, s] = self.catalog().units;
+        let secs = duration.num_seconds().max(0);
+        let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+        if days > 0 {
+            format!("{}{} {}{}", days, d, hours, h)
+        } else if hours > 0 {
+            format!("{}{} {:02}{}", hours, h, mins, m)
+        } else {
+            format!("{}{} {:02}{}", mins, m, secs, s)
+        }
+    }
+}
+
+/// Replace each `{}` in `template` with the next of `args`
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut parts = template.split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+    for (part, arg) in parts.zip(
+        args.iter()
+            .map(|arg| arg.to_string())
+            .chain(std::iter::repeat(String::new())),
+    ) {
+        filled.push_str(&arg);
+        filled.push_str(part);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fill_templates() {
+        assert_eq!(fill("{} overtook {}!", &[&"A", &"B"]), "A overtook B!");
+        assert_eq!(fill("Top {}", &[&3]), "Top 3");
+        assert_eq!(fill("no args", &[]), "no args");
+    }
+
+    #[test]
+    fn ordinals() {
+        let en: Vec<String> = [1, 2, 3, 4, 11, 12, 13, 21, 22, 23]
+            .iter()
+            .map(|n| Locale::En.ordinal(*n))
+            .collect();
+        assert_eq!(
+            en,
+            vec!["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "23rd"]
+        );
+        let sv: Vec<String> = [1, 2, 3, 11, 12, 21, 22]
+            .iter()
+            .map(|n| Locale::Sv.ordinal(*n))
+            .collect();
+        assert_eq!(
+            sv,
+            vec!["1:a", "2:a", "3:e", "11:e", "12:e", "21:a", "22:a"]
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Locale::En.number(1234567), "1,234,567");
+        assert_eq!(Locale::En.number(123), "123");
+        assert_eq!(Locale::Sv.number(1234), "1\u{00A0}234");
+        assert_eq!(Locale::Sv.percent(0.5), "50\u{00A0}%");
+    }
+
+    #[test]
+    fn lists() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(Locale::En.list(&names(&[])), "");
+        assert_eq!(Locale::En.list(&names(&["A"])), "A");
+        assert_eq!(Locale::En.list(&names(&["A", "B", "C"])), "A, B and C");
+        assert_eq!(Locale::Sv.list(&names(&["A", "B"])), "A och B");
+    }
+
+    #[test]
+    fn durations() {
+        let secs = chrono::Duration::seconds;
+        assert_eq!(Locale::En.duration(secs(245)), "4m 05s");
+        assert_eq!(Locale::En.duration(secs(3 * 3600 + 12 * 60)), "3h 12m");
+        assert_eq!(Locale::En.duration(secs(90000)), "1d 1h");
+        assert_eq!(
+            Locale::Sv.duration(secs(3 * 3600 + 12 * 60)),
+            "3 tim 12 min"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Leaderboard progression over time, rebuilt from the star timestamps
+use crate::score::{Points, Score, ScoringStrategy, StarCount};
+use crate::time::TimeStamp;
+use crate::{AocData, PlayerId};
+use num::Zero;
+use std::collections::{BTreeMap, HashMap};
+
+/// Value to follow over time
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Metric {
+    Points,
+    /// Position in the standings, 1 is the leader.
+    Rank,
+}
+
+/// Value of a metric for a player at each time it changed, earliest first
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub values: Vec<(TimeStamp, u32)>,
+}
+
+impl AocData {
+    /// Progression of `metric` for the `top_n` best players according to `strategy`
+    ///
+    /// The points of each star are given by `strategy` and do not change with later stars,
+    /// so the score at any time is the sum over the stars acquired so far.
+    /// Ranks are among all players, with the same ordering as the standings.
+    /// The series are ordered by the current standings, best first.
+    pub fn progression(
+        &self,
+        strategy: &dyn ScoringStrategy,
+        metric: Metric,
+        top_n: usize,
+    ) -> Vec<Series> {
+        let scores = self.score_progression(strategy);
+        let mut rank_progressions = match metric {
+            Metric::Points => HashMap::new(),
+            Metric::Rank => rank_progressions(&scores),
+        };
+        let ranks = self.ranks(strategy);
+        let mut top: Vec<&PlayerId> = ranks.keys().collect();
+        top.sort_by_key(|id| (ranks[*id], &self.players[*id].name));
+        top.truncate(top_n);
+        top.into_iter()
+            .map(|id| {
+                let values = match metric {
+                    Metric::Points => scores[id]
+                        .iter()
+                        .map(|(ts, score)| (*ts, score.points.0))
+                        .collect(),
+                    Metric::Rank => rank_progressions.remove(id).unwrap_or_default(),
+                };
+                Series {
+                    name: self.players[id].name.clone(),
+                    values,
+                }
+            })
+            .collect()
+    }
+
+    /// Score of each player after each of their stars, earliest first
+    fn score_progression(
+        &self,
+        strategy: &dyn ScoringStrategy,
+    ) -> HashMap<PlayerId, Vec<(TimeStamp, Score)>> {
+        let mut points = strategy.points(self);
+        self.players
+            .iter()
+            .map(|(id, pl)| {
+                let star_points = points.remove(id).unwrap_or_default();
+                let mut stars: Vec<(TimeStamp, Points)> = pl
+                    .completion_day_level
+                    .iter()
+                    .flat_map(|(day, dc)| dc.stars().map(move |(part, ts)| (*day, part, ts)))
+                    .map(|(day, part, ts)| {
+                        let points = star_points.get(&(day, part)).copied();
+                        (ts, points.unwrap_or_else(Points::zero))
+                    })
+                    .collect();
+                stars.sort_by_key(|(ts, _)| *ts);
+                let mut score = Score {
+                    stars: StarCount(0),
+                    points: Points::zero(),
+                };
+                let progression = stars
+                    .into_iter()
+                    .map(|(ts, points)| {
+                        score = Score {
+                            stars: StarCount(score.stars.0 + 1),
+                            points: score.points + points,
+                        };
+                        (ts, score)
+                    })
+                    .collect();
+                (*id, progression)
+            })
+            .collect()
+    }
+}
+
+/// Rank of each player each time any player acquired a star, from the player's first star
+///
+/// All players are ranked in one sweep over the stars in time order, keeping only the changes of
+/// each player's rank.
+fn rank_progressions(
+    scores: &HashMap<PlayerId, Vec<(TimeStamp, Score)>>,
+) -> HashMap<PlayerId, Vec<(TimeStamp, u32)>> {
+    let mut stars: Vec<(TimeStamp, PlayerId, Score)> = scores
+        .iter()
+        .flat_map(|(id, progression)| {
+            progression
+                .iter()
+                .map(move |(ts, score)| (*ts, *id, *score))
+        })
+        .collect();
+    stars.sort_by_key(|(ts, _, _)| *ts);
+    let mut current: HashMap<PlayerId, Score> = HashMap::new();
+    // Number of players with each score
+    let mut counts: BTreeMap<Score, u32> = BTreeMap::new();
+    let mut ranks: HashMap<PlayerId, Vec<(TimeStamp, u32)>> = HashMap::new();
+    for (i, (ts, id, score)) in stars.iter().enumerate() {
+        if let Some(prev) = current.insert(*id, *score) {
+            if let Some(count) = counts.get_mut(&prev) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(&prev);
+                }
+            }
+        }
+        *counts.entry(*score).or_default() += 1;
+        // Rank once all stars with the same timestamp are counted.
+        if stars.get(i + 1).map(|(next, _, _)| next) == Some(ts) {
+            continue;
+        }
+        let mut better = 0;
+        let mut score_ranks: BTreeMap<Score, u32> = BTreeMap::new();
+        for (score, count) in counts.iter().rev() {
+            score_ranks.insert(*score, better + 1);
+            better += count;
+        }
+        for (id, score) in &current {
+            let rank = score_ranks[score];
+            let progression = ranks.entry(*id).or_default();
+            if progression.last().map(|(_, last)| *last) != Some(rank) {
+                progression.push((*ts, rank));
+            }
+        }
+    }
+    ranks
+}
@@ -0,0 +1,231 @@
+//! Rendering of tables in different output formats
+//!
+//! Standings, diffs and per-day stars are first built as a [`Table`],
+//! which any [`Render`] backend can then turn into text.
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use unicode_width::UnicodeWidthStr;
+
+/// Titled table of text cells
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Table {
+    pub title: String,
+    pub header: Vec<String>,
+    /// Rows with one cell for each column in `header`
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(title: &str, header: &[&str]) -> Self {
+        Table {
+            title: title.to_string(),
+            header: header.iter().map(|col| col.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+}
+
+/// Output format backend
+pub trait Render {
+    fn render(&self, table: &Table) -> String;
+}
+
+/// Monospace text table, with the columns aligned by their display width
+#[derive(Copy, Clone, Debug)]
+pub struct Text;
+
+impl Render for Text {
+    fn render(&self, table: &Table) -> String {
+        let mut widths: Vec<usize> = table.header.iter().map(|col| col.width()).collect();
+        for row in &table.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.width());
+            }
+        }
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.width())))
+                .collect();
+            format!("{}\n", cells.join(" | ").trim_end())
+        };
+        let mut text = format!("{}\n", table.title);
+        text.push_str(&line(&table.header));
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        text.push_str(&format!("{}\n", rule.join("-+-")));
+        for row in &table.rows {
+            text.push_str(&line(row));
+        }
+        text
+    }
+}
+
+/// Markdown table, with the title in bold
+#[derive(Copy, Clone, Debug)]
+pub struct Markdown;
+
+impl Render for Markdown {
+    fn render(&self, table: &Table) -> String {
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+            format!("| {} |\n", cells.join(" | "))
+        };
+        let mut md = format!("**{}**\n\n", table.title);
+        md.push_str(&line(&table.header));
+        md.push_str(&format!("|{}\n", "---|".repeat(table.header.len())));
+        for row in &table.rows {
+            md.push_str(&line(row));
+        }
+        md
+    }
+}
+
+/// Standalone HTML document
+#[derive(Copy, Clone, Debug)]
+pub struct Html;
+
+impl Render for Html {
+    fn render(&self, table: &Table) -> String {
+        let line = |tag: &str, cells: &[String]| {
+            let cells: String = cells
+                .iter()
+                .map(|cell| format!("<{0}>{1}</{0}>", tag, escape_html(cell)))
+                .collect();
+            format!("<tr>{}</tr>\n", cells)
+        };
+        let title = escape_html(&table.title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<table>\n<caption>{0}</caption>\n",
+            title
+        );
+        html.push_str(&line("th", &table.header));
+        for row in &table.rows {
+            html.push_str(&line("td", row));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+/// Comma separated values, with the header as the first line and without the title
+#[derive(Copy, Clone, Debug)]
+pub struct Csv;
+
+impl Render for Csv {
+    fn render(&self, table: &Table) -> String {
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|cell| escape_csv(cell)).collect();
+            format!("{}\n", cells.join(","))
+        };
+        let mut csv = line(&table.header);
+        for row in &table.rows {
+            csv.push_str(&line(row));
+        }
+        csv
+    }
+}
+
+/// JSON object with the title and the rows as objects keyed by the header
+#[derive(Copy, Clone, Debug)]
+pub struct Json;
+
+impl Render for Json {
+    fn render(&self, table: &Table) -> String {
+        let rows: Vec<Value> = table
+            .rows
+            .iter()
+            .map(|row| {
+                let fields: Map<String, Value> = table
+                    .header
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().map(|cell| Value::String(cell.clone())))
+                    .collect();
+                Value::Object(fields)
+            })
+            .collect();
+        json!({ "title": table.title, "rows": rows }).to_string()
+    }
+}
+
+/// Configurable choice of backend
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    #[default]
+    Text,
+    Markdown,
+    Html,
+    Csv,
+    Json,
+}
+
+impl Render for Format {
+    fn render(&self, table: &Table) -> String {
+        match self {
+            Format::Text => Text.render(table),
+            Format::Markdown => Markdown.render(table),
+            Format::Html => Html.render(table),
+            Format::Csv => Csv.render(table),
+            Format::Json => Json.render(table),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Quote a field if it contains a separator, a quote or a line break
+fn escape_csv(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new("Standings", &["#", "Player"]);
+        table.push(vec!["1".to_string(), "A, \"B\" <C>".to_string()]);
+        table
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(Csv.render(&table()), "#,Player\n1,\"A, \"\"B\"\" <C>\"\n");
+        assert!(Html
+            .render(&table())
+            .contains("<td>A, &quot;B&quot; &lt;C&gt;</td>"));
+        let json: Value = serde_json::from_str(&Json.render(&table())).unwrap();
+        assert_eq!(json["rows"][0]["Player"], "A, \"B\" <C>");
+    }
+
+    #[test]
+    fn text_table() {
+        assert_eq!(
+            Text.render(&table()),
+            "Standings\n# | Player\n--+-----------\n1 | A, \"B\" <C>\n"
+        );
+    }
+
+    #[test]
+    fn markdown_table() {
+        assert_eq!(
+            Markdown.render(&table()),
+            "**Standings**\n\n| # | Player |\n|---|---|\n| 1 | A, \"B\" <C> |\n"
+        );
+    }
+}
@@ -1,13 +1,435 @@
+use crate::locale::{fill, Locale};
+use crate::time::{de_hh_mm, de_opt_hh_mm, Day, TimeStamp};
+use crate::{AocData, Part, PlayerId};
+use chrono::{DateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use derive_more::{Add, Display};
+use itertools::Itertools;
 use num::Zero;
 use serde::{Deserialize, Serialize};
 use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
 use std::iter::Sum;
 
+/// Points for each star, keyed by (day, part)
+pub type StarPoints = BTreeMap<(Day, Part), Points>;
+
+/// Strategy for awarding points to players
+///
+/// Points are awarded per star, so that the stars of a single day or part can be treated
+/// separately.
+pub trait ScoringStrategy {
+    /// Short label for the points, used in formatting.
+    fn label(&self) -> &'static str;
+
+    /// Points for each acquired star of each player
+    ///
+    /// Players without points may be left out.
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints>;
+
+    /// Days or parts that give no points for the event of `data`
+    fn excluded(&self, _data: &AocData) -> Vec<Exclusion> {
+        Vec::new()
+    }
+}
+
+/// A day, or a single part of a day, excluded from scoring
+///
+/// E.g. day 1 of 2020, which gave no points because of a server outage.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Exclusion {
+    pub day: Day,
+    /// The whole day is excluded if missing.
+    #[serde(default)]
+    pub part: Option<Part>,
+}
+
+impl Exclusion {
+    pub fn contains(&self, day: Day, part: Part) -> bool {
+        self.day == day && self.part.map_or(true, |excl_part| excl_part == part)
+    }
+
+    pub fn fmt(&self, locale: Locale) -> String {
+        let cat = locale.catalog();
+        match self.part {
+            Some(part) => fill(cat.excluded_part, &[&self.day, &part]),
+            None => fill(cat.excluded_day, &[&self.day]),
+        }
+    }
+}
+
+/// Exclusions for each event, keyed by the name of the event, e.g. "2020"
+pub type Exclusions = HashMap<String, Vec<Exclusion>>;
+
+/// Any strategy, but with no points for the excluded days or parts
+///
+/// The stars of the excluded days still count.
+#[derive(Clone, Debug)]
+pub struct Excluding<S> {
+    pub strategy: S,
+    pub excluded: Exclusions,
+}
+
+impl<S: ScoringStrategy> ScoringStrategy for Excluding<S> {
+    fn label(&self) -> &'static str {
+        self.strategy.label()
+    }
+
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints> {
+        let excluded = self.excluded(data);
+        let mut points = self.strategy.points(data);
+        points.values_mut().for_each(|star_points| {
+            star_points.retain(|(day, part), _| !excluded.iter().any(|ex| ex.contains(*day, *part)))
+        });
+        points
+    }
+
+    fn excluded(&self, data: &AocData) -> Vec<Exclusion> {
+        let mut excluded = self.strategy.excluded(data);
+        excluded.extend(self.excluded.get(data.event.trim()).into_iter().flatten());
+        excluded.sort_unstable();
+        excluded.dedup();
+        excluded
+    }
+}
+
+/// The official AoC local score
+///
+/// For each star, the first player to get it gets one point per player on the leaderboard,
+/// the second gets one point less and so on.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct OfficialScore;
+
+impl ScoringStrategy for OfficialScore {
+    fn label(&self) -> &'static str {
+        "ls"
+    }
+
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints> {
+        data.local_scores()
+            .into_iter()
+            .map(|(id, day_scores)| {
+                let points = day_scores
+                    .into_iter()
+                    .flat_map(|(day, (ls_1, ls_2))| {
+                        vec![
+                            ((day, Part::One), ls_1.into()),
+                            ((day, Part::Two), ls_2.into()),
+                        ]
+                    })
+                    .filter(|(_, points): &(_, Points)| !points.is_zero())
+                    .collect();
+                (id, points)
+            })
+            .collect()
+    }
+}
+
+/// One point per star
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StarsOnly;
+
+impl ScoringStrategy for StarsOnly {
+    fn label(&self) -> &'static str {
+        "stars"
+    }
+
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints> {
+        star_points(data, |_, _, _, _| Points(1))
+    }
+}
+
+/// Points for solving quickly after the puzzle unlocks
+///
+/// Each star gives one point per minute left of `cap` when it was acquired,
+/// i.e. a star acquired `cap` or later after the unlock gives no points.
+/// Players get no points if the event is not a year.
+#[derive(Copy, Clone, Debug)]
+pub struct TimeSinceUnlock {
+    pub cap: chrono::Duration,
+}
+
+impl ScoringStrategy for TimeSinceUnlock {
+    fn label(&self) -> &'static str {
+        "pts"
+    }
+
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints> {
+        let year = match data.year() {
+            Ok(year) => year,
+            Err(_) => return HashMap::new(),
+        };
+        star_points(data, |_, day, _, ts| {
+            capped_points(ts.duration_since_unlock(day, year), self.cap)
+        })
+    }
+}
+
+/// Points for solving part 2 quickly after part 1
+///
+/// Each completed day gives one point per minute left of `cap` when part 2 was acquired,
+/// counted from the time of part 1. The points are awarded to the part 2 star.
+#[derive(Copy, Clone, Debug)]
+pub struct Part2Delta {
+    pub cap: chrono::Duration,
+}
+
+impl ScoringStrategy for Part2Delta {
+    fn label(&self) -> &'static str {
+        "pts"
+    }
+
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints> {
+        data.players()
+            .map(|(id, pl)| {
+                let points = pl
+                    .completion_day_level
+                    .iter()
+                    .filter_map(|(day, dc)| {
+                        let star_2 = dc.star_2?;
+                        let delta = star_2.ts.duration_since(dc.star_1.ts);
+                        Some(((*day, Part::Two), capped_points(delta, self.cap)))
+                    })
+                    .collect();
+                (*id, points)
+            })
+            .collect()
+    }
+}
+
+/// Selectable scoring strategy, e.g. from a config file
+///
+/// Time caps are given in hours.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Scoring {
+    #[default]
+    LocalScore,
+    Stars,
+    TimeSinceUnlock {
+        cap_hours: u32,
+    },
+    #[serde(rename = "part_2_delta", alias = "part2_delta")]
+    Part2Delta {
+        cap_hours: u32,
+    },
+}
+
+impl Scoring {
+    fn strategy(&self) -> Box<dyn ScoringStrategy> {
+        match *self {
+            Scoring::LocalScore => Box::new(OfficialScore),
+            Scoring::Stars => Box::new(StarsOnly),
+            Scoring::TimeSinceUnlock { cap_hours } => Box::new(TimeSinceUnlock {
+                cap: chrono::Duration::hours(i64::from(cap_hours)),
+            }),
+            Scoring::Part2Delta { cap_hours } => Box::new(Part2Delta {
+                cap: chrono::Duration::hours(i64::from(cap_hours)),
+            }),
+        }
+    }
+}
+
+impl ScoringStrategy for Scoring {
+    fn label(&self) -> &'static str {
+        self.strategy().label()
+    }
+
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints> {
+        self.strategy().points(data)
+    }
+}
+
+/// Which time the solves are measured from in [`TimeZoneFair`]
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FairStart {
+    /// The player's wake-up time after the unlock
+    #[default]
+    WakeUp,
+    /// The player's part 1 star, i.e. only the time from part 1 to part 2 counts.
+    Part1,
+}
+
+/// Time zone and wake-up time of a player
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct PlayerTime {
+    pub time_zone: Tz,
+    /// Overrides the default wake-up time
+    #[serde(default, deserialize_with = "de_opt_hh_mm")]
+    pub wake_up: Option<NaiveTime>,
+}
+
+/// Points for solving quickly after the player's own start time
+///
+/// The official unlock time favours players in some time zones.
+/// Here, each solve is instead measured from the player's first wake-up time after the unlock,
+/// in their own time zone. Solves before the wake-up time give full points.
+/// Otherwise the points are awarded as in [`TimeSinceUnlock`].
+///
+/// With [`FairStart::Part1`], the solves are scored as in [`Part2Delta`] instead.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TimeZoneFair {
+    pub cap_hours: u32,
+    #[serde(default)]
+    pub start: FairStart,
+    /// Wake-up time for players without their own, defaults to 07:00
+    #[serde(default = "default_wake_up", deserialize_with = "de_hh_mm")]
+    pub wake_up: NaiveTime,
+    /// Time zone for players without their own, defaults to UTC
+    #[serde(default = "default_time_zone")]
+    pub time_zone: Tz,
+    #[serde(default)]
+    pub players: HashMap<PlayerId, PlayerTime>,
+}
+
+fn default_wake_up() -> NaiveTime {
+    NaiveTime::from_hms_opt(7, 0, 0).expect("Invalid time")
+}
+
+fn default_time_zone() -> Tz {
+    Tz::UTC
+}
+
+impl TimeZoneFair {
+    /// Start time of a player for `day`
+    ///
+    /// The first occurrence of the player's wake-up time, in their time zone, at or after the
+    /// unlock. Falls back to the unlock if the wake-up time does not exist that day,
+    /// e.g. in a DST gap.
+    pub fn start(&self, id: &PlayerId, day: Day, year: i32) -> DateTime<Utc> {
+        let player = self.players.get(id);
+        let tz = player.map(|pl| pl.time_zone).unwrap_or(self.time_zone);
+        let wake_up = player.and_then(|pl| pl.wake_up).unwrap_or(self.wake_up);
+        let unlock = day.unlock(year);
+        let local_date = unlock.with_timezone(&tz).date_naive();
+        [local_date, local_date.succ_opt().unwrap_or(local_date)]
+            .iter()
+            .filter_map(|date| tz.from_local_datetime(&date.and_time(wake_up)).earliest())
+            .map(|start| start.with_timezone(&Utc))
+            .find(|start| *start >= unlock)
+            .unwrap_or(unlock)
+    }
+
+    fn cap(&self) -> chrono::Duration {
+        chrono::Duration::hours(i64::from(self.cap_hours))
+    }
+}
+
+impl ScoringStrategy for TimeZoneFair {
+    fn label(&self) -> &'static str {
+        "fair"
+    }
+
+    fn points(&self, data: &AocData) -> HashMap<PlayerId, StarPoints> {
+        if self.start == FairStart::Part1 {
+            return Part2Delta { cap: self.cap() }.points(data);
+        }
+        let year = match data.year() {
+            Ok(year) => year,
+            Err(_) => return HashMap::new(),
+        };
+        star_points(data, |id, day, _, ts| {
+            let start = TimeStamp::from(self.start(id, day, year));
+            capped_points(ts.duration_since(start), self.cap())
+        })
+    }
+}
+
+/// Award points to each acquired star of each player with
+/// `star_fn(player id, day, part, timestamp)`
+fn star_points<F>(data: &AocData, star_fn: F) -> HashMap<PlayerId, StarPoints>
+where
+    F: Fn(&PlayerId, Day, Part, TimeStamp) -> Points,
+{
+    data.players()
+        .map(|(id, pl)| {
+            let points = pl
+                .completion_day_level
+                .iter()
+                .flat_map(|(day, dc)| dc.stars().map(move |(part, ts)| (*day, part, ts)))
+                .map(|(day, part, ts)| ((day, part), star_fn(id, day, part, ts)))
+                .collect();
+            (*id, points)
+        })
+        .collect()
+}
+
+/// One point per whole minute left of `cap` after `elapsed`
+///
+/// A negative `elapsed`, e.g. a star before a player's wake-up time, gives the full `cap`.
+fn capped_points(elapsed: chrono::Duration, cap: chrono::Duration) -> Points {
+    let elapsed = elapsed.max(chrono::Duration::zero());
+    let left = (cap - elapsed).num_minutes().max(0);
+    Points(u32::try_from(left).unwrap_or(u32::MAX))
+}
+
+/// Special cases in the AoC local score for a player on a day
+#[derive(Copy, Clone, Debug, Display, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SpecialCase {
+    /// The player has a star with the exact same timestamp as another player.
+    #[display(fmt = "tie")]
+    Tie,
+    /// The day, or a part of it, gives no points.
+    #[display(fmt = "excluded")]
+    Excluded,
+    /// The player likely joined the leaderboard later and caught up on the day, see
+    /// [`AocData::verify_scores`].
+    #[display(fmt = "joined later")]
+    JoinedLater,
+}
+
+/// Difference between the local score reported by the API and the recomputed one
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScoreMismatch {
+    pub name: String,
+    /// Local score reported by the API
+    pub expected: LocalScore,
+    /// Local score computed from `completion_day_level`
+    pub computed: LocalScore,
+    /// Days where the player's score involves a special case, which are the likely culprits.
+    pub days: BTreeMap<Day, Vec<SpecialCase>>,
+}
+
+impl std::fmt::Display for ScoreMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, computed {}",
+            self.name, self.expected, self.computed
+        )?;
+        if self.days.is_empty() {
+            return write!(f, " (no special cases)");
+        }
+        let days: Vec<String> = self
+            .days
+            .iter()
+            .map(|(day, cases)| format!("day {}: {}", day, cases.iter().join(" and ")))
+            .collect();
+        write!(f, " ({})", days.join(", "))
+    }
+}
+
+/// Result of verifying the recomputed local scores, see [`AocData::verify_scores`]
+#[derive(Clone, Debug, Default)]
+pub struct ScoreReport {
+    pub mismatches: Vec<ScoreMismatch>,
+}
+
+impl ScoreReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Standing of a player
+///
+/// Players are ordered by their number of stars, then by their points.
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub struct Score {
     pub stars: StarCount,
-    pub local: LocalScore,
+    pub points: Points,
 }
 
 impl PartialOrd for Score {
@@ -20,7 +442,7 @@ impl Ord for Score {
     fn cmp(&self, other: &Self) -> Ordering {
         self.stars
             .cmp(&other.stars)
-            .then(self.local.cmp(&other.local))
+            .then(self.points.cmp(&other.points))
     }
 }
 
@@ -62,3 +484,33 @@ impl Zero for GlobalScore {
         self.0 == 0
     }
 }
+
+/// Points according to a [`ScoringStrategy`]
+#[derive(
+    Add, Copy, Clone, Debug, Display, Deserialize, Serialize, Ord, PartialOrd, Eq, PartialEq,
+)]
+pub struct Points(pub(crate) u32);
+
+impl Zero for Points {
+    fn zero() -> Self {
+        Points(0)
+    }
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl Sum<Self> for Points {
+    fn sum<I>(iter: I) -> Self
+    where
+        I: Iterator<Item = Self>,
+    {
+        iter.fold(Self(0), |a, b| Self(a.0 + b.0))
+    }
+}
+
+impl From<LocalScore> for Points {
+    fn from(ls: LocalScore) -> Self {
+        Points(ls.0)
+    }
+}
@@ -0,0 +1,406 @@
+//! Statistics on the players' solve times
+use crate::locale::{fill, Locale};
+use crate::render::Table;
+use crate::score::StarCount;
+use crate::time::Day;
+use crate::{AocData, AocError, DayCompletion, Player, PlayerId};
+use chrono::Duration;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+/// Mean and median of a set of durations
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Summary {
+    pub mean: Duration,
+    pub median: Duration,
+}
+
+impl Summary {
+    /// Summarise `durations`, `None` if there are none.
+    pub fn new(mut durations: Vec<Duration>) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort();
+        let n = durations.len();
+        let total: Duration = durations.iter().fold(Duration::zero(), |acc, d| acc + *d);
+        let median = if n % 2 == 0 {
+            (durations[n / 2 - 1] + durations[n / 2]) / 2
+        } else {
+            durations[n / 2]
+        };
+        Some(Summary {
+            mean: total / n as i32,
+            median,
+        })
+    }
+
+    pub fn fmt(&self, locale: Locale) -> String {
+        let cat = locale.catalog();
+        format!(
+            "{} {}, {} {}",
+            cat.median,
+            locale.duration(self.median),
+            cat.mean,
+            locale.duration(self.mean)
+        )
+    }
+}
+
+/// Solve time statistics for a player
+///
+/// Times are measured from the unlock of each day's puzzle.
+/// A day is completed when both parts are solved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlayerStats {
+    pub name: String,
+    /// Time from unlock to part 1
+    pub part_1: Option<Summary>,
+    /// Time from unlock to part 2
+    pub part_2: Option<Summary>,
+    /// Time from part 1 to part 2
+    pub delta: Option<Summary>,
+    /// Completed day with the shortest time from unlock to part 2
+    pub fastest_day: Option<(Day, Duration)>,
+    /// Completed day with the longest time from unlock to part 2
+    pub slowest_day: Option<(Day, Duration)>,
+    /// Number of days completed before the next puzzle unlocked
+    pub release_days: usize,
+    /// Longest run of consecutive completed days
+    pub longest_streak: usize,
+}
+
+impl PlayerStats {
+    pub(crate) fn new(player: &Player, year: i32) -> Self {
+        let days = &player.completion_day_level;
+        let since_unlock = |day: Day, dc: &DayCompletion| {
+            let part_1 = dc.star_1.ts.duration_since_unlock(day, year);
+            let part_2 = dc
+                .star_2
+                .map(|star_2| star_2.ts.duration_since_unlock(day, year));
+            (part_1, part_2)
+        };
+        let part_1 = days
+            .iter()
+            .map(|(day, dc)| since_unlock(*day, dc).0)
+            .collect();
+        let completed: BTreeMap<Day, Duration> = days
+            .iter()
+            .filter_map(|(day, dc)| Some((*day, since_unlock(*day, dc).1?)))
+            .collect();
+        let delta = days
+            .values()
+            .filter_map(|dc| Some(dc.star_2?.ts.duration_since(dc.star_1.ts)))
+            .collect();
+        PlayerStats {
+            name: player.name.clone(),
+            part_1: Summary::new(part_1),
+            part_2: Summary::new(completed.values().copied().collect()),
+            delta: Summary::new(delta),
+            fastest_day: completed
+                .iter()
+                .min_by_key(|(_, t)| **t)
+                .map(|(d, t)| (*d, *t)),
+            slowest_day: completed
+                .iter()
+                .max_by_key(|(_, t)| **t)
+                .map(|(d, t)| (*d, *t)),
+            release_days: completed
+                .values()
+                .filter(|t| **t < Duration::days(1))
+                .count(),
+            longest_streak: longest_streak(completed.keys().copied()),
+        }
+    }
+
+    pub fn fmt(&self, locale: Locale) -> String {
+        let cat = locale.catalog();
+        let mut fmt_stats = format!("{}\n", fill(cat.stats, &[&self.name]));
+        let summaries = [
+            (cat.part_1, &self.part_1),
+            (cat.part_2, &self.part_2),
+            (cat.part_1_to_2, &self.delta),
+        ];
+        for (label, summary) in summaries.iter() {
+            if let Some(summary) = summary {
+                fmt_stats.push_str(&format!("{}: {}\n", label, summary.fmt(locale)));
+            }
+        }
+        let days = [
+            (cat.fastest_day, self.fastest_day),
+            (cat.slowest_day, self.slowest_day),
+        ];
+        for (label, day) in days.iter() {
+            if let Some((day, time)) = day {
+                fmt_stats.push_str(&format!(
+                    "{}: {} ({})\n",
+                    label,
+                    day,
+                    locale.duration(*time)
+                ));
+            }
+        }
+        fmt_stats.push_str(&format!(
+            "{}: {}\n{}: {}\n",
+            cat.release_days, self.release_days, cat.longest_streak, self.longest_streak
+        ));
+        fmt_stats
+    }
+}
+
+/// Time from unlock to each part of a day's puzzle
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SolveTimes {
+    pub part_1: Duration,
+    pub part_2: Option<Duration>,
+}
+
+/// Solve times of a player for each day with at least one star
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolveTimesRow {
+    pub name: String,
+    pub days: BTreeMap<Day, SolveTimes>,
+}
+
+/// Statistics on a day's puzzle across the leaderboard
+///
+/// Solve times are measured from the unlock to part 2.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DayStats {
+    pub day: Day,
+    /// Number of players with part 1
+    pub part_1: usize,
+    /// Number of players with part 2
+    pub part_2: usize,
+    pub median: Option<Duration>,
+    /// Time between the fastest and the slowest solve
+    pub spread: Option<Duration>,
+}
+
+impl DayStats {
+    /// Share of the part 1 solvers who also solved part 2
+    pub fn completion_share(&self) -> f64 {
+        if self.part_1 == 0 {
+            return 0.0;
+        }
+        self.part_2 as f64 / self.part_1 as f64
+    }
+
+    /// Order by difficulty, hardest first
+    ///
+    /// Days with a lower share of part 2 solvers are harder,
+    /// ties are broken by the median solve time.
+    fn cmp_difficulty(&self, other: &Self) -> Ordering {
+        // Compare the shares without floats: a / b < c / d <=> a * d < c * b
+        (self.part_2 * other.part_1)
+            .cmp(&(other.part_2 * self.part_1))
+            .then_with(|| other.median.cmp(&self.median))
+    }
+
+    pub fn fmt(&self, locale: Locale) -> String {
+        let cat = locale.catalog();
+        let mut fmt_stats = format!(
+            "{} {}: {}",
+            cat.day,
+            self.day,
+            fill(
+                cat.day_stats,
+                &[
+                    &self.part_2,
+                    &self.part_1,
+                    &locale.percent(self.completion_share())
+                ]
+            )
+        );
+        if let (Some(median), Some(spread)) = (self.median, self.spread) {
+            fmt_stats.push_str(&format!(
+                ", {} {}, {} {}",
+                cat.median,
+                locale.duration(median),
+                cat.spread,
+                locale.duration(spread)
+            ));
+        }
+        fmt_stats.push('\n');
+        fmt_stats
+    }
+}
+
+/// Format the `n` hardest days
+pub fn fmt_hardest(days: &[DayStats], n: usize, locale: Locale) -> String {
+    let mut fmt_days = format!("{}:\n", locale.catalog().hardest);
+    for (pos, day) in days.iter().take(n).enumerate() {
+        fmt_days.push_str(&format!("{} {}", pos + 1, day.fmt(locale)));
+    }
+    fmt_days
+}
+
+impl AocData {
+    /// Solve time statistics for all players
+    pub fn player_stats(&self) -> Result<HashMap<PlayerId, PlayerStats>, AocError> {
+        let year = self.year()?;
+        Ok(self
+            .players()
+            .map(|(id, pl)| (*id, PlayerStats::new(pl, year)))
+            .collect())
+    }
+
+    /// Median solve times, release days and longest streak of all players as a table
+    ///
+    /// Players are ordered by the number of stars, then by name.
+    pub fn stats_table(&self, locale: Locale) -> Result<Table, AocError> {
+        let cat = locale.catalog();
+        let mut stats: Vec<(StarCount, PlayerStats)> = self
+            .player_stats()?
+            .into_iter()
+            .map(|(id, stats)| (self.players[&id].stars, stats))
+            .collect();
+        stats.sort_by(|(a_stars, a), (b_stars, b)| b_stars.cmp(a_stars).then(a.name.cmp(&b.name)));
+        let header = [
+            cat.player,
+            cat.part_1,
+            cat.part_2,
+            cat.part_1_to_2,
+            cat.release_days,
+            cat.longest_streak,
+        ];
+        let mut table = Table::new(cat.player_stats, &header);
+        let median = |summary: Option<Summary>| {
+            summary
+                .map(|summary| locale.duration(summary.median))
+                .unwrap_or_default()
+        };
+        for (_, stats) in stats {
+            table.push(vec![
+                stats.name,
+                median(stats.part_1),
+                median(stats.part_2),
+                median(stats.delta),
+                stats.release_days.to_string(),
+                stats.longest_streak.to_string(),
+            ]);
+        }
+        Ok(table)
+    }
+
+    /// Solve times of all players, with the most stars first
+    ///
+    /// Players with the same number of stars are ordered by name.
+    pub fn solve_times(&self) -> Result<Vec<SolveTimesRow>, AocError> {
+        let year = self.year()?;
+        let mut rows: Vec<(StarCount, SolveTimesRow)> = self
+            .players()
+            .map(|(_, pl)| {
+                let days = pl
+                    .completion_day_level
+                    .iter()
+                    .map(|(day, dc)| {
+                        let times = SolveTimes {
+                            part_1: dc.star_1.ts.duration_since_unlock(*day, year),
+                            part_2: dc
+                                .star_2
+                                .map(|star_2| star_2.ts.duration_since_unlock(*day, year)),
+                        };
+                        (*day, times)
+                    })
+                    .collect();
+                let row = SolveTimesRow {
+                    name: pl.name.clone(),
+                    days,
+                };
+                (pl.stars, row)
+            })
+            .collect();
+        rows.sort_by(|(a_stars, a), (b_stars, b)| b_stars.cmp(a_stars).then(a.name.cmp(&b.name)));
+        Ok(rows.into_iter().map(|(_, row)| row).collect())
+    }
+
+    /// Statistics for each day with at least one star, hardest first
+    pub fn day_stats(&self) -> Result<Vec<DayStats>, AocError> {
+        let year = self.year()?;
+        let mut days: Vec<DayStats> = Day::all()
+            .filter_map(|day| {
+                let dcs: Vec<&DayCompletion> = self
+                    .players()
+                    .filter_map(|(_, pl)| pl.completion_day_level.get(&day))
+                    .collect();
+                if dcs.is_empty() {
+                    return None;
+                }
+                let mut times: Vec<Duration> = dcs
+                    .iter()
+                    .filter_map(|dc| Some(dc.star_2?.ts.duration_since_unlock(day, year)))
+                    .collect();
+                times.sort();
+                Some(DayStats {
+                    day,
+                    part_1: dcs.len(),
+                    part_2: times.len(),
+                    median: Summary::new(times.clone()).map(|summary| summary.median),
+                    spread: times
+                        .first()
+                        .zip(times.last())
+                        .map(|(min, max)| *max - *min),
+                })
+            })
+            .collect();
+        days.sort_by(DayStats::cmp_difficulty);
+        Ok(days)
+    }
+}
+
+/// Length of the longest run of consecutive days in `days`, which must be sorted.
+fn longest_streak(days: impl Iterator<Item = Day>) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut prev: Option<Day> = None;
+    for day in days {
+        current = match prev {
+            Some(prev) if prev.0 + 1 == day.0 => current + 1,
+            _ => 1,
+        };
+        longest = longest.max(current);
+        prev = Some(day);
+    }
+    longest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary_median() {
+        let mins = |ms: &[i64]| ms.iter().map(|m| Duration::minutes(*m)).collect();
+        let odd = Summary::new(mins(&[30, 10, 20])).unwrap();
+        assert_eq!(odd.median, Duration::minutes(20));
+        assert_eq!(odd.mean, Duration::minutes(20));
+        let even = Summary::new(mins(&[10, 20, 40, 90])).unwrap();
+        assert_eq!(even.median, Duration::minutes(30));
+        assert_eq!(even.mean, Duration::minutes(40));
+        assert!(Summary::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn streaks() {
+        let days = |ds: &[u32]| ds.iter().map(|d| Day(*d)).collect::<Vec<_>>().into_iter();
+        assert_eq!(longest_streak(days(&[])), 0);
+        assert_eq!(longest_streak(days(&[1, 2, 3, 5, 6])), 3);
+        assert_eq!(longest_streak(days(&[2, 4, 5, 6, 7, 9])), 4);
+    }
+
+    #[test]
+    fn difficulty_order() {
+        let day = |day, part_1, part_2, median| DayStats {
+            day: Day(day),
+            part_1,
+            part_2,
+            median: Some(Duration::minutes(median)),
+            spread: None,
+        };
+        let mut days = [day(1, 10, 10, 20), day(2, 10, 5, 60), day(3, 4, 2, 90)];
+        days.sort_by(DayStats::cmp_difficulty);
+        let order: Vec<u32> = days.iter().map(|day| day.day.0).collect();
+        assert_eq!(order, vec![3, 2, 1]);
+    }
+}
@@ -0,0 +1,184 @@
+//! Text renderings of the leaderboard, for channels without images
+//!
+//! All renderings use single width characters, to line up in a monospace code block.
+use crate::progression::Metric;
+use crate::score::ScoringStrategy;
+use crate::time::{Day, TimeStamp};
+use crate::{AocData, AocError, DayCompletion, EARNED_STAR_SYMBOL, PART_1_SYMBOL};
+use std::collections::BTreeMap;
+
+/// Symbol for a day without stars
+const NO_STAR_SYMBOL: char = '\u{00B7}';
+/// Sparkline levels, lowest first
+const SPARKS: [char; 8] = [
+    '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}',
+];
+/// Full block, for histogram bars
+const BAR_SYMBOL: char = '\u{2588}';
+/// Light shade, for the part of a histogram bar with only part 1
+const PART_1_BAR_SYMBOL: char = '\u{2591}';
+/// Width of the longest histogram bar
+const BAR_WIDTH: usize = 20;
+
+/// One symbol for each of the `days` first calendar days
+///
+/// Both parts give a full star, part 1 only a hollow star.
+fn star_bar(completion: &BTreeMap<Day, DayCompletion>, days: usize) -> String {
+    Day::all()
+        .take(days)
+        .map(|day| match completion.get(&day) {
+            Some(dc) if dc.star_2.is_some() => EARNED_STAR_SYMBOL,
+            Some(_) => PART_1_SYMBOL,
+            None => NO_STAR_SYMBOL,
+        })
+        .collect()
+}
+
+/// Sparkline of `ranks` among `num_players`, where the leader gets the highest level
+///
+/// Missing ranks are left blank.
+fn rank_sparkline(ranks: &[Option<u32>], num_players: u32) -> String {
+    ranks
+        .iter()
+        .map(|rank| match rank {
+            Some(rank) if num_players > 1 => {
+                let level = (num_players - rank) as usize * (SPARKS.len() - 1);
+                SPARKS[level / (num_players - 1) as usize]
+            }
+            Some(_) => SPARKS[SPARKS.len() - 1],
+            None => ' ',
+        })
+        .collect()
+}
+
+/// Length of a bar for `count` out of `max`
+fn bar_len(count: usize, max: usize) -> usize {
+    (count * BAR_WIDTH).checked_div(max).unwrap_or(0)
+}
+
+/// Bar of length proportional to `count` out of `max`
+fn bar(count: usize, max: usize) -> String {
+    std::iter::repeat(BAR_SYMBOL)
+        .take(bar_len(count, max))
+        .collect()
+}
+
+impl AocData {
+    /// Last day with at least one star, i.e. the number of calendar days to render
+    fn days_played(&self) -> usize {
+        self.players()
+            .filter_map(|(_, pl)| pl.completion_day_level.keys().last())
+            .max()
+            .map(|day| day.0 as usize)
+            .unwrap_or(0)
+    }
+
+    /// Star bar for each player, with the most stars first
+    pub fn fmt_star_bars(&self) -> String {
+        let days = self.days_played();
+        let mut players: Vec<_> = self.players().map(|(_, pl)| pl).collect();
+        players.sort_by(|a, b| b.stars.cmp(&a.stars).then(a.name.cmp(&b.name)));
+        players
+            .iter()
+            .map(|pl| {
+                format!(
+                    "{: <20} {}\n",
+                    pl.name,
+                    star_bar(&pl.completion_day_level, days)
+                )
+            })
+            .collect()
+    }
+
+    /// Rank sparkline for the `top_n` best players according to `strategy`
+    ///
+    /// Each day gives the rank when the next puzzle unlocked, or now for the latest day.
+    pub fn fmt_rank_sparklines(
+        &self,
+        strategy: &dyn ScoringStrategy,
+        top_n: usize,
+    ) -> Result<String, AocError> {
+        let year = self.year()?;
+        let mut day_ends: Vec<TimeStamp> = Day::all()
+            .take(self.days_played())
+            .map(|day| (day.unlock(year) + chrono::Duration::days(1)).into())
+            .collect();
+        if let Some(last) = day_ends.last_mut() {
+            *last = TimeStamp(u64::MAX);
+        }
+        Ok(self
+            .progression(strategy, Metric::Rank, top_n)
+            .iter()
+            .map(|series| {
+                let ranks: Vec<Option<u32>> = day_ends
+                    .iter()
+                    .map(|end| {
+                        series
+                            .values
+                            .iter()
+                            .take_while(|(ts, _)| ts < end)
+                            .last()
+                            .map(|(_, rank)| *rank)
+                    })
+                    .collect();
+                format!(
+                    "{: <20} {}\n",
+                    series.name,
+                    rank_sparkline(&ranks, self.num_players() as u32)
+                )
+            })
+            .collect())
+    }
+
+    /// Number of solvers of each part for each day, as a histogram
+    ///
+    /// The bars show the players with both stars, followed by the players with only part 1.
+    pub fn fmt_solver_histogram(&self) -> String {
+        let counts: Vec<(Day, usize, usize)> = Day::all()
+            .take(self.days_played())
+            .map(|day| {
+                let dcs = self
+                    .players()
+                    .filter_map(|(_, pl)| pl.completion_day_level.get(&day));
+                let (part_1, part_2) = dcs.fold((0, 0), |(part_1, part_2), dc| {
+                    (part_1 + 1, part_2 + dc.star_2.iter().count())
+                });
+                (day, part_1, part_2)
+            })
+            .collect();
+        let max = self.num_players();
+        counts
+            .iter()
+            .map(|(day, part_1, part_2)| {
+                let both = bar(*part_2, max);
+                let only_1: String = std::iter::repeat(PART_1_BAR_SYMBOL)
+                    .take(bar_len(*part_1, max) - bar_len(*part_2, max))
+                    .collect();
+                format!("{: >2} {}{} {}/{}\n", day, both, only_1, part_2, part_1)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sparkline_levels() {
+        let ranks = [None, Some(1), Some(2), Some(8), Some(5)];
+        assert_eq!(
+            rank_sparkline(&ranks, 8),
+            " \u{2588}\u{2587}\u{2581}\u{2584}"
+        );
+        assert_eq!(rank_sparkline(&[Some(1)], 1), "\u{2588}");
+    }
+
+    #[test]
+    fn bar_lengths() {
+        assert_eq!(bar(0, 10), "");
+        assert_eq!(bar(10, 10).chars().count(), BAR_WIDTH);
+        assert_eq!(bar(5, 10).chars().count(), BAR_WIDTH / 2);
+        assert_eq!(bar(1, 0), "");
+    }
+}
@@ -1,12 +1,35 @@
+use crate::locale::{fill, Locale};
 use crate::AocError;
 use chrono::prelude::*;
 use chrono::Local;
+use chrono_tz::{America, Tz};
 use derive_more::Display;
 use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use std::cmp::Ordering;
 use std::time::{Duration, UNIX_EPOCH};
 
+/// Year of the first Advent of Code
+pub const FIRST_EVENT_YEAR: i32 = 2015;
+/// Latest year accepted for an event, far within the dates that can be represented
+pub const LAST_EVENT_YEAR: i32 = 9999;
+
+/// Check that `year` can be the year of an event, so that its unlock times exist
+pub fn check_year(year: i32) -> Result<i32, AocError> {
+    if (FIRST_EVENT_YEAR..=LAST_EVENT_YEAR).contains(&year) {
+        Ok(year)
+    } else {
+        Err(AocError::Param {
+            param: "year".to_string(),
+            val: year.to_string(),
+            reason: format!(
+                "Year not between {} and {}",
+                FIRST_EVENT_YEAR, LAST_EVENT_YEAR
+            ),
+        })
+    }
+}
+
 #[derive(
     Copy, Clone, Debug, Display, Hash, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize,
 )]
@@ -24,9 +47,46 @@ impl Day {
             })
         }
     }
+
+    /// All days in the event calendar
+    pub fn all() -> impl Iterator<Item = Day> {
+        (1..=25).map(Day)
+    }
+
+    /// Last day in the event calendar
+    pub fn last() -> Day {
+        Day(25)
+    }
+
+    /// Time at which the puzzle for this day is released
+    ///
+    /// Puzzles unlock at midnight in America/New_York.
+    /// `year` is an event year, as checked by [`check_year`], e.g. from [`crate::AocData::year`].
+    pub fn unlock(self, year: i32) -> DateTime<Utc> {
+        America::New_York
+            .with_ymd_and_hms(year, 12, self.0, 0, 0, 0)
+            .single()
+            .expect("Midnight in December of an event year is never in a DST transition")
+            .with_timezone(&Utc)
+    }
+
+    /// The day whose puzzle was unlocked within the 24 hours before `now`, if any
+    pub fn active(year: i32, now: DateTime<Utc>) -> Option<Day> {
+        Day::all().find(|day| {
+            let unlock = day.unlock(year);
+            unlock <= now && now < unlock + chrono::Duration::days(1)
+        })
+    }
+
+    /// The next day to unlock after `now` and the time until its unlock, `None` after day 25
+    pub fn next_unlock(year: i32, now: DateTime<Utc>) -> Option<(Day, chrono::Duration)> {
+        Day::all()
+            .find(|day| day.unlock(year) > now)
+            .map(|day| (day, day.unlock(year) - now))
+    }
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, Serialize, Ord, PartialOrd, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, Ord, PartialOrd, PartialEq, Eq, Hash)]
 pub struct TimeStamp(pub(crate) u64);
 
 impl TimeStamp {
@@ -34,6 +94,31 @@ impl TimeStamp {
         TimeStamp(ts)
     }
 
+    /// Time elapsed from `earlier` to `self`, negative if `earlier` is later.
+    pub fn duration_since(self, earlier: TimeStamp) -> chrono::Duration {
+        chrono::Duration::seconds(self.0 as i64 - earlier.0 as i64)
+    }
+
+    /// Time elapsed from the unlock of `day` in the event of `year`
+    pub fn duration_since_unlock(self, day: Day, year: i32) -> chrono::Duration {
+        self.duration_since(day.unlock(year).into())
+    }
+
+    /// Local date and time in `time_zone`, or in the server's time zone if `None`
+    pub fn local(self, time_zone: Option<Tz>) -> NaiveDateTime {
+        match time_zone {
+            Some(tz) => DateTime::<Utc>::from(self).with_timezone(&tz).naive_local(),
+            None => DateTime::<Local>::from(self).naive_local(),
+        }
+    }
+
+    /// Discord timestamp markup, which each viewer sees in their own time zone
+    ///
+    /// `style` is one of Discord's styles, e.g. 't' for the time of day or 'R' for relative time.
+    pub fn discord(self, style: char) -> String {
+        format!("<t:{}:{}>", self.0, style)
+    }
+
     pub fn hour_and_minute(self) -> (u32, u32) {
         let dt: DateTime<Local> = self.into();
         (dt.hour(), dt.minute())
@@ -50,7 +135,7 @@ impl TimeStamp {
 impl std::fmt::Display for TimeStamp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (hour, min) = self.hour_and_minute();
-        write!(f, "{}:{}", hour, min)
+        write!(f, "{:02}:{:02}", hour, min)
     }
 }
 
@@ -61,6 +146,74 @@ impl From<TimeStamp> for DateTime<Local> {
     }
 }
 
+impl From<TimeStamp> for DateTime<Utc> {
+    fn from(ts: TimeStamp) -> Self {
+        let d = UNIX_EPOCH + Duration::from_secs(ts.0);
+        DateTime::<Utc>::from(d)
+    }
+}
+
+impl From<DateTime<Utc>> for TimeStamp {
+    fn from(dt: DateTime<Utc>) -> Self {
+        TimeStamp(dt.timestamp().max(0) as u64)
+    }
+}
+
+/// Format of displayed timestamps
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeFormat {
+    /// Time of day, e.g. "21:05"
+    #[default]
+    Clock,
+    /// Date and time of day, e.g. "2021-12-05 21:05"
+    DateTime,
+    /// Time from the puzzle unlock, e.g. "3h 12m after unlock"
+    Relative,
+    /// Discord timestamp markup, shown in the local time of each viewer
+    Discord,
+}
+
+/// Time zone and format of displayed timestamps
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+pub struct TimeDisplay {
+    /// IANA time zone, e.g. "Europe/Stockholm". The server's time zone if missing.
+    #[serde(default)]
+    pub time_zone: Option<Tz>,
+    #[serde(default)]
+    pub format: TimeFormat,
+}
+
+impl TimeDisplay {
+    /// Format `ts`, a star for the puzzle of `day`
+    pub fn fmt(self, ts: TimeStamp, day: Day, locale: Locale) -> String {
+        match self.format {
+            TimeFormat::Clock => locale.time(ts.local(self.time_zone)),
+            TimeFormat::DateTime => locale.date_time(ts.local(self.time_zone)),
+            TimeFormat::Relative => {
+                let since_unlock = ts.duration_since_unlock(day, event_year(ts, day));
+                fill(
+                    locale.catalog().after_unlock,
+                    &[&locale.duration(since_unlock)],
+                )
+            }
+            TimeFormat::Discord => ts.discord('t'),
+        }
+    }
+}
+
+/// Year of the most recent unlock of `day` at or before `ts`
+///
+/// Assumes that a star is acquired within a year of its unlock.
+fn event_year(ts: TimeStamp, day: Day) -> i32 {
+    let year = DateTime::<Utc>::from(ts).year();
+    if day.unlock(year) <= DateTime::<Utc>::from(ts) {
+        year
+    } else {
+        year - 1
+    }
+}
+
 pub(crate) fn sort_optional_ts(a: &Option<TimeStamp>, b: &Option<TimeStamp>) -> Ordering {
     match (a, b) {
         (Some(a), Some(b)) => a.cmp(b),
@@ -70,6 +223,30 @@ pub(crate) fn sort_optional_ts(a: &Option<TimeStamp>, b: &Option<TimeStamp>) ->
     }
 }
 
+/// Parse a time of day given as "HH:MM", optionally with seconds
+fn parse_hh_mm(time: &str) -> Result<NaiveTime, chrono::ParseError> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(time.trim(), "%H:%M:%S"))
+}
+
+/// Special parsing of a time of day, see [`parse_hh_mm`]
+pub fn de_hh_mm<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let time = String::deserialize(deserializer)?;
+    parse_hh_mm(&time).map_err(|err| de::Error::custom(format!("time parsing: {}", err)))
+}
+
+/// Special parsing of an optional time of day, see [`parse_hh_mm`]
+pub fn de_opt_hh_mm<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveTime>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(time) => parse_hh_mm(&time)
+            .map(Some)
+            .map_err(|err| de::Error::custom(format!("time parsing: {}", err))),
+        None => Ok(None),
+    }
+}
+
 /// Special parsing of `TimeStamp`
 ///
 /// The timestamps in the `completion_day_level` fields, a missing `get_star_ts` field are set to `null`,
@@ -119,3 +296,78 @@ pub(crate) fn de_opt_timestamp<'de, D: Deserializer<'de>>(
         _ => Err(de::Error::custom("wrong type")),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unlock_at_midnight_eastern() {
+        let unlock = Day::try_new(1).unwrap().unlock(2020);
+        assert_eq!(unlock, Utc.with_ymd_and_hms(2020, 12, 1, 5, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn event_years() {
+        assert_eq!(check_year(2020).ok(), Some(2020));
+        assert!(check_year(1999).is_err());
+        assert!(check_year(i32::MAX).is_err());
+    }
+
+    #[test]
+    fn active_and_next_day() {
+        let at = |month, day, hour| Utc.with_ymd_and_hms(2021, month, day, hour, 0, 0).unwrap();
+        let day = |day| Day::try_new(day).ok();
+        assert_eq!(Day::active(2021, at(11, 30, 12)), None);
+        assert_eq!(Day::active(2021, at(12, 1, 5)), day(1));
+        assert_eq!(Day::active(2021, at(12, 2, 4)), day(1));
+        assert_eq!(Day::active(2021, at(12, 26, 4)), day(25));
+        assert_eq!(Day::active(2021, at(12, 26, 5)), None);
+        assert_eq!(
+            Day::next_unlock(2021, at(12, 2, 4)),
+            Some((Day(2), chrono::Duration::hours(1)))
+        );
+        assert_eq!(Day::next_unlock(2021, at(12, 25, 5)), None);
+    }
+
+    #[test]
+    fn since_unlock() {
+        let ts = TimeStamp::from(Utc.with_ymd_and_hms(2021, 12, 5, 8, 5, 9).unwrap());
+        assert_eq!(
+            ts.duration_since_unlock(Day(5), 2021),
+            chrono::Duration::seconds(3 * 3600 + 5 * 60 + 9)
+        );
+    }
+
+    #[test]
+    fn time_display_formats() {
+        let day = Day::try_new(5).unwrap();
+        // 2021-12-05 08:05:09 UTC, 3h 05m after the unlock.
+        let ts = TimeStamp(1638691509);
+        let display = |time_zone: &str, format| TimeDisplay {
+            time_zone: Some(time_zone.parse().unwrap()),
+            format,
+        };
+        let fmt = |display: TimeDisplay| display.fmt(ts, day, Locale::Sv);
+        assert_eq!(fmt(display("UTC", TimeFormat::Clock)), "08:05");
+        assert_eq!(fmt(display("Europe/Stockholm", TimeFormat::Clock)), "09:05");
+        assert_eq!(
+            fmt(display("America/New_York", TimeFormat::DateTime)),
+            "2021-12-05 03:05"
+        );
+        assert_eq!(
+            display("UTC", TimeFormat::Relative).fmt(ts, day, Locale::En),
+            "3h 05m after unlock"
+        );
+        assert_eq!(fmt(display("UTC", TimeFormat::Discord)), "<t:1638691509:t>");
+    }
+
+    #[test]
+    fn relative_to_the_latest_unlock() {
+        let day = Day::try_new(5).unwrap();
+        let january = TimeStamp::from(Utc.with_ymd_and_hms(2022, 1, 2, 0, 0, 0).unwrap());
+        assert_eq!(event_year(january, day), 2021);
+        let december = TimeStamp::from(Utc.with_ymd_and_hms(2021, 12, 6, 0, 0, 0).unwrap());
+        assert_eq!(event_year(december, day), 2021);
+    }
+}
@@ -0,0 +1,147 @@
+//! Head-to-head comparison between two players
+use crate::locale::{fill, Locale};
+use crate::time::{Day, TimeStamp};
+use crate::{AocData, AocError, DayCompletion, Part, PlayerId};
+use chrono::Duration;
+use std::cmp::Ordering;
+
+/// One of the two players in a head-to-head comparison
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Side {
+    A,
+    B,
+}
+
+/// Race between two players to a single star
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Duel {
+    pub day: Day,
+    pub part: Part,
+    pub a: Option<TimeStamp>,
+    pub b: Option<TimeStamp>,
+}
+
+impl Duel {
+    /// The player who got the star first, `None` for a tie or if neither has the star.
+    pub fn winner(&self) -> Option<Side> {
+        match (self.a, self.b) {
+            (Some(a), Some(b)) => match a.cmp(&b) {
+                Ordering::Less => Some(Side::A),
+                Ordering::Greater => Some(Side::B),
+                Ordering::Equal => None,
+            },
+            (Some(_), None) => Some(Side::A),
+            (None, Some(_)) => Some(Side::B),
+            (None, None) => None,
+        }
+    }
+
+    /// Time between the two stars, `None` unless both players have the star.
+    pub fn margin(&self) -> Option<Duration> {
+        Some(self.a?.duration_since(self.b?).abs())
+    }
+}
+
+/// Comparison of two players on the days both have attempted
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HeadToHead {
+    pub a: String,
+    pub b: String,
+    /// Duels for every part of the days both players have attempted, ordered by day and part.
+    pub duels: Vec<Duel>,
+    /// Local score of `a` minus the local score of `b`
+    pub local_score_gap: i64,
+}
+
+impl HeadToHead {
+    /// Number of duels won by `a` and by `b`, and number of ties
+    pub fn record(&self) -> (usize, usize, usize) {
+        self.duels
+            .iter()
+            .filter(|duel| duel.a.is_some() || duel.b.is_some())
+            .fold((0, 0, 0), |(a, b, ties), duel| match duel.winner() {
+                Some(Side::A) => (a + 1, b, ties),
+                Some(Side::B) => (a, b + 1, ties),
+                None => (a, b, ties + 1),
+            })
+    }
+
+    fn name(&self, side: Side) -> &str {
+        match side {
+            Side::A => &self.a,
+            Side::B => &self.b,
+        }
+    }
+
+    pub fn fmt(&self, locale: Locale) -> String {
+        let cat = locale.catalog();
+        let (wins_a, wins_b, ties) = self.record();
+        let mut fmt_h2h = format!(
+            "{} vs {}: {} - {} ({} {})\n",
+            self.a, self.b, wins_a, wins_b, ties, cat.ties
+        );
+        for duel in &self.duels {
+            let result = match (duel.winner(), duel.margin()) {
+                (Some(side), Some(margin)) => {
+                    fill(cat.won_by, &[&self.name(side), &locale.duration(margin)])
+                }
+                (Some(side), None) => fill(cat.unanswered, &[&self.name(side)]),
+                (None, Some(_)) => cat.tie.to_string(),
+                (None, None) => continue,
+            };
+            fmt_h2h.push_str(&format!(
+                "{} {: <2} {} {}: {}\n",
+                cat.day, duel.day, cat.part, duel.part, result
+            ));
+        }
+        fmt_h2h.push_str(&format!(
+            "{}: {:+}\n",
+            cat.local_score_gap, self.local_score_gap
+        ));
+        fmt_h2h
+    }
+}
+
+impl AocData {
+    /// Compare the players `a` and `b` on every day both have attempted
+    pub fn head_to_head(&self, a: &PlayerId, b: &PlayerId) -> Result<HeadToHead, AocError> {
+        let player = |id: &PlayerId| {
+            self.players.get(id).ok_or_else(|| AocError::Param {
+                param: "player".to_string(),
+                val: id.to_string(),
+                reason: "No such player in the leaderboard".to_string(),
+            })
+        };
+        let (pl_a, pl_b) = (player(a)?, player(b)?);
+        let duels = pl_a
+            .completion_day_level
+            .iter()
+            .filter_map(|(day, dc_a)| Some((*day, dc_a, pl_b.completion_day_level.get(day)?)))
+            .flat_map(|(day, dc_a, dc_b)| {
+                let star = |dc: &DayCompletion, part| {
+                    dc.stars().find(|(p, _)| *p == part).map(|(_, ts)| ts)
+                };
+                [Part::One, Part::Two].map(|part| Duel {
+                    day,
+                    part,
+                    a: star(dc_a, part),
+                    b: star(dc_b, part),
+                })
+            })
+            .collect();
+        Ok(HeadToHead {
+            a: pl_a.name.clone(),
+            b: pl_b.name.clone(),
+            duels,
+            local_score_gap: i64::from(pl_a.local_score.0) - i64::from(pl_b.local_score.0),
+        })
+    }
+
+    /// Id of the player called `name`, ignoring case
+    pub fn player_id(&self, name: &str) -> Option<PlayerId> {
+        self.players
+            .iter()
+            .find(|(_, pl)| pl.name.eq_ignore_ascii_case(name))
+            .map(|(id, _)| *id)
+    }
+}
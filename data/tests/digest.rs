@@ -0,0 +1,56 @@
+//! Test Digest impl based on files `time_1.json`, `time_2.json`
+//!
+//! See `full_diff.rs` for the changes between the files.
+
+use aoc_data::digest::Digest;
+use aoc_data::locale::Locale;
+use aoc_data::score::OfficialScore;
+use aoc_data::time::{Day, TimeDisplay};
+use aoc_data::{get_local_data, AocData};
+
+#[test]
+fn check_digest() {
+    let earlier: AocData =
+        get_local_data("tests/data/time_1.json").expect("File: 'time_1.json' missing");
+    let later: AocData =
+        get_local_data("tests/data/time_2.json").expect("File: 'time_2.json' missing");
+    let digest = Digest::new(
+        &later,
+        &earlier,
+        Day::try_new(17).unwrap(),
+        3,
+        &OfficialScore,
+    );
+
+    // Only Niklas has new stars for day 17.
+    let solvers: Vec<&String> = digest.solvers().map(|(name, _)| name).collect();
+    assert_eq!(solvers, vec!["Niklas"]);
+    assert!(digest.fastest_part_2().is_some());
+    assert!(digest
+        .fmt(Locale::En, TimeDisplay::default())
+        .contains("Top 3:"));
+    let sv = digest.fmt(Locale::Sv, TimeDisplay::default());
+    assert!(sv.starts_with("Dagens sammanfattning, dag 17"));
+    assert!(sv.contains("Topp 3:"));
+    assert_eq!(digest.hardest().count(), 0);
+    assert!(!sv.contains("Sammanfattning av eventet"));
+}
+
+#[test]
+fn last_day_recap() {
+    let data: AocData =
+        get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing");
+    let digest = Digest::new(&data, &data, Day::last(), 3, &OfficialScore);
+    let hardest: Vec<Day> = data
+        .day_stats()
+        .unwrap()
+        .iter()
+        .map(|stats| stats.day)
+        .collect();
+    assert_eq!(
+        digest.hardest().map(|stats| stats.day).collect::<Vec<_>>(),
+        hardest
+    );
+    let fmt_digest = digest.fmt(Locale::En, TimeDisplay::default());
+    assert!(fmt_digest.contains("End of event recap\nHardest days:\n1 Day "));
+}
@@ -19,9 +19,9 @@
 //! Remove stars:
 //! - Jern, Day 12, star 2
 
-use aoc_data::diff::{Diff, NewStars};
+use aoc_data::diff::{Diff, NewStars, Star, StarStatus};
 use aoc_data::time::{Day, TimeStamp};
-use aoc_data::{get_local_data, AocData};
+use aoc_data::{get_local_data, AocData, Part};
 use std::collections::HashMap;
 use std::panic;
 
@@ -73,7 +73,12 @@ fn check_stars() {
                 String::from("Jern"),
                 vec![(
                     Day::try_new(12).unwrap(),
-                    NewStars::new(vec![TimeStamp::new(1608328926)]),
+                    NewStars::new(vec![Star::new(
+                        Part::One,
+                        TimeStamp::new(1608328926),
+                        11,
+                        StarStatus::New,
+                    )]),
                 )],
             ),
             (
@@ -81,11 +86,17 @@ fn check_stars() {
                 vec![
                     (
                         Day::try_new(1).unwrap(),
-                        NewStars::new(vec![TimeStamp::new(1608492670), TimeStamp::new(1608492671)]),
+                        NewStars::new(vec![
+                            Star::new(Part::One, TimeStamp::new(1608492670), 15, StarStatus::New),
+                            Star::new(Part::Two, TimeStamp::new(1608492671), 15, StarStatus::New),
+                        ]),
                     ),
                     (
                         Day::try_new(17).unwrap(),
-                        NewStars::new(vec![TimeStamp::new(1608492668), TimeStamp::new(1608492669)]),
+                        NewStars::new(vec![
+                            Star::new(Part::One, TimeStamp::new(1608492668), 10, StarStatus::New),
+                            Star::new(Part::Two, TimeStamp::new(1608492669), 10, StarStatus::New),
+                        ]),
                     ),
                 ],
             ),
@@ -94,11 +105,17 @@ fn check_stars() {
                 vec![
                     (
                         Day::try_new(4).unwrap(),
-                        NewStars::new(vec![TimeStamp::new(1607266996), TimeStamp::new(1608874817)]),
+                        NewStars::new(vec![
+                            Star::new(Part::One, TimeStamp::new(1607266996), 13, StarStatus::Known),
+                            Star::new(Part::Two, TimeStamp::new(1608874817), 13, StarStatus::New),
+                        ]),
                     ),
                     (
                         Day::try_new(16).unwrap(),
-                        NewStars::new(vec![TimeStamp::new(1608874815), TimeStamp::new(1608874816)]),
+                        NewStars::new(vec![
+                            Star::new(Part::One, TimeStamp::new(1608874815), 11, StarStatus::New),
+                            Star::new(Part::Two, TimeStamp::new(1608874816), 9, StarStatus::New),
+                        ]),
                     ),
                 ],
             ),
@@ -3,7 +3,7 @@
 //! The file `time_2.json` contains data for the 2020 comptetition where the local score for day 1
 //! was ignored
 
-use aoc_data::score::LocalScore;
+use aoc_data::score::{Exclusions, LocalScore, SpecialCase};
 use aoc_data::time::Day;
 use aoc_data::{get_local_data, AocData, PlayerId};
 use std::cmp::Reverse;
@@ -60,3 +60,55 @@ where
 
     assert!(result.is_ok())
 }
+
+#[test]
+fn verify_scores_report() {
+    let data: AocData =
+        get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing");
+    let excluded: Exclusions = serde_json::from_str(r#"{"2020": [{"day": 1}]}"#).unwrap();
+    let report = data.verify_scores(&excluded);
+    let day_1 = Day::try_new(1).unwrap();
+    let day_2 = Day::try_new(2).unwrap();
+    let day_22 = Day::try_new(22).unwrap();
+    for mismatch in &report.mismatches {
+        assert_ne!(mismatch.expected, mismatch.computed);
+        let excluded = mismatch
+            .days
+            .get(&day_1)
+            .is_some_and(|cases| cases.contains(&SpecialCase::Excluded));
+        assert!(excluded, "{}: day 1 not excluded", mismatch);
+    }
+    let mismatch = |name: &str| {
+        report
+            .mismatches
+            .iter()
+            .find(|m| m.name == name)
+            .unwrap_or_else(|| panic!("{} mismatch", name))
+    };
+    assert_eq!(
+        mismatch("Jacko").days.get(&day_22),
+        Some(&vec![SpecialCase::Tie])
+    );
+    assert_eq!(
+        mismatch("Nes").days.get(&day_22),
+        Some(&vec![SpecialCase::Tie])
+    );
+    assert_eq!(
+        mismatch("Broms").days.get(&day_2),
+        Some(&vec![SpecialCase::JoinedLater])
+    );
+
+    // Without the exclusion, the points of day 1 are unexplained.
+    let unexcluded = data.verify_scores(&Exclusions::new());
+    let andreas = unexcluded
+        .mismatches
+        .iter()
+        .find(|m| m.name == "Andreas")
+        .expect("Andreas mismatch");
+    assert!(andreas.days.is_empty());
+    assert!(unexcluded
+        .mismatches
+        .iter()
+        .flat_map(|m| m.days.values().flatten())
+        .all(|case| *case != SpecialCase::Excluded));
+}
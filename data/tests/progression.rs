@@ -0,0 +1,35 @@
+//! Test the leaderboard progression on the file `test_data.json`
+
+use aoc_data::progression::Metric;
+use aoc_data::score::OfficialScore;
+use aoc_data::{get_local_data, AocData};
+
+fn test_data() -> AocData {
+    get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing")
+}
+
+#[test]
+fn points_end_at_current_score() {
+    let data = test_data();
+    let series = data.progression(&OfficialScore, Metric::Points, 3);
+    assert_eq!(series.len(), 3);
+    for ((name, score), series) in data.scores(&OfficialScore).iter().zip(series.iter()) {
+        assert_eq!(*name, series.name);
+        let (_, last) = series.values.last().unwrap();
+        assert_eq!(score.points.to_string(), last.to_string());
+        assert!(series.values.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
+
+#[test]
+fn ranks_end_at_current_rank() {
+    let data = test_data();
+    let series = data.progression(&OfficialScore, Metric::Rank, data.num_players());
+    let ranks = data.ranks(&OfficialScore);
+    for series in series.iter().filter(|series| !series.values.is_empty()) {
+        let id = data.player_id(&series.name).unwrap();
+        let (_, last) = series.values.last().unwrap();
+        assert_eq!(*last as usize, ranks[&id]);
+        assert!(series.values.windows(2).all(|pair| pair[0].1 != pair[1].1));
+    }
+}
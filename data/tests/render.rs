@@ -0,0 +1,92 @@
+//! Test rendering of standings and diffs on the files `time_1.json` and `time_2.json`
+
+use aoc_data::locale::Locale;
+use aoc_data::render::{Csv, Format, Json, Markdown, Render, Text};
+use aoc_data::score::OfficialScore;
+use aoc_data::time::{Day, TimeDisplay};
+use aoc_data::{get_local_data, AocData};
+use serde_json::Value;
+
+fn data() -> (AocData, AocData) {
+    let prev = get_local_data("tests/data/time_1.json").expect("File: 'time_1.json' missing");
+    let latest = get_local_data("tests/data/time_2.json").expect("File: 'time_2.json' missing");
+    (latest, prev)
+}
+
+#[test]
+fn standings_in_all_formats() {
+    let (latest, _) = data();
+    let table = latest.scores_table(&OfficialScore, Locale::En);
+    assert_eq!(table.rows.len(), latest.num_players());
+    assert_eq!(table.header, vec!["#", "Player", "Stars", "ls"]);
+
+    let csv = Csv.render(&table);
+    assert_eq!(csv.lines().count(), latest.num_players() + 1);
+    let json: Value = serde_json::from_str(&Json.render(&table)).unwrap();
+    assert_eq!(json["rows"][0]["#"], "1");
+    assert!(Markdown
+        .render(&table)
+        .starts_with("**Standings**\n\n| # | Player | Stars | ls |\n"));
+    assert!(Text.render(&table).starts_with("Standings\n"));
+    assert_eq!(Format::default().render(&table), Text.render(&table));
+}
+
+#[test]
+fn day_standings() {
+    let (latest, prev) = data();
+    let day = Day::try_new(1).unwrap();
+    let table = latest.day_table(day, Locale::En, TimeDisplay::default());
+    assert_eq!(
+        table.header,
+        vec!["#", "Player", "Stars", "Times", "Podium"]
+    );
+    assert_eq!(table.rows.len(), 15);
+    let positions: Vec<&str> = table.rows.iter().map(|row| row[0].as_str()).collect();
+    assert_eq!(positions[..3], ["1", "2", "3"]);
+    assert_eq!(table.rows[0][1], "Klintan");
+    assert_eq!(table.rows[0][4], "\u{1F947} first to finish Day 1");
+    // Players who solved the day later are in the standings but not in the diff.
+    let diff = latest.diff(&prev).expect("Expected a diff");
+    let new = diff.day_table(day, Locale::En, TimeDisplay::default());
+    assert!(new.rows.len() < table.rows.len());
+}
+
+#[test]
+fn diff_tables() {
+    let (latest, prev) = data();
+    let diff = latest.diff(&prev).expect("Expected a diff");
+    let table = diff.table(Locale::En, TimeDisplay::default());
+    let rows: usize = diff.new_stars().map(|(_, stars)| stars.len()).sum();
+    assert_eq!(table.rows.len(), rows);
+    assert_eq!(
+        table.header,
+        vec!["Player", "Day", "Stars", "Times", "Podium"]
+    );
+    let days: usize = diff
+        .days()
+        .into_iter()
+        .map(|day: Day| {
+            diff.day_table(day, Locale::En, TimeDisplay::default())
+                .rows
+                .len()
+        })
+        .sum();
+    assert_eq!(days, rows);
+    assert_eq!(
+        diff.ranks_table(Locale::En).rows.len(),
+        diff.rank_changes().count()
+    );
+    assert_eq!(
+        diff.new_players_table(Locale::En).rows.len(),
+        diff.new_players().count()
+    );
+
+    // Each non-empty table is rendered, separated by an empty line.
+    let tables = diff.tables(Locale::En, TimeDisplay::default());
+    assert!(tables.iter().all(|table| !table.rows.is_empty()));
+    let rendered: Vec<String> = tables.iter().map(|table| Markdown.render(table)).collect();
+    assert_eq!(
+        diff.fmt(&Markdown, Locale::En, TimeDisplay::default()),
+        rendered.join("\n")
+    );
+}
@@ -0,0 +1,157 @@
+//! Test scoring strategies on the file `test_data.json`
+
+use aoc_data::locale::Locale;
+use aoc_data::render::Text;
+use aoc_data::score::{
+    Excluding, Exclusions, OfficialScore, Part2Delta, Scoring, ScoringStrategy, StarsOnly,
+    TimeSinceUnlock, TimeZoneFair,
+};
+use aoc_data::time::Day;
+use aoc_data::{get_local_data, AocData, PlayerId};
+use chrono::{TimeZone, Utc};
+
+fn test_data() -> AocData {
+    get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing")
+}
+
+#[test]
+fn stars_only_matches_star_count() {
+    let data = test_data();
+    for (_, score) in data.scores(&StarsOnly) {
+        assert_eq!(score.stars.to_string(), score.points.to_string());
+    }
+}
+
+#[test]
+fn strategies_rank_all_players() {
+    let data = test_data();
+    let cap = chrono::Duration::hours(24);
+    for strategy in [
+        &OfficialScore as &dyn ScoringStrategy,
+        &StarsOnly,
+        &TimeSinceUnlock { cap },
+        &Part2Delta { cap },
+        &fair(),
+    ] {
+        let scores = data.scores(strategy);
+        assert_eq!(scores.len(), data.num_players());
+        assert!(scores.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+}
+
+#[test]
+fn parse_scoring() {
+    let scoring: Scoring =
+        serde_json::from_str(r#"{"kind": "time_since_unlock", "cap_hours": 24}"#).unwrap();
+    assert_eq!(scoring, Scoring::TimeSinceUnlock { cap_hours: 24 });
+    let scoring: Scoring = serde_json::from_str(r#"{"kind": "local_score"}"#).unwrap();
+    assert_eq!(scoring, Scoring::default());
+    let scoring: Scoring =
+        serde_json::from_str(r#"{"kind": "part_2_delta", "cap_hours": 12}"#).unwrap();
+    assert_eq!(scoring, Scoring::Part2Delta { cap_hours: 12 });
+    let scoring: Scoring =
+        serde_json::from_str(r#"{"kind": "part2_delta", "cap_hours": 12}"#).unwrap();
+    assert_eq!(scoring, Scoring::Part2Delta { cap_hours: 12 });
+}
+
+fn fair() -> TimeZoneFair {
+    serde_json::from_str(
+        r#"{
+            "cap_hours": 24,
+            "players": {
+                "1": {"time_zone": "Europe/Stockholm"},
+                "2": {"time_zone": "America/Los_Angeles", "wake_up": "06:30"}
+            }
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn time_zone_fair_start() {
+    let fair = fair();
+    let day = Day::try_new(1).unwrap();
+    let player = |id: &str| serde_json::from_str::<PlayerId>(id).unwrap();
+    // Unlock at 06:00 CET, start at the default wake-up time the same morning.
+    assert_eq!(
+        fair.start(&player("1"), day, 2020),
+        Utc.with_ymd_and_hms(2020, 12, 1, 6, 0, 0).unwrap()
+    );
+    // Unlock at 21:00 PST the evening before, start the next morning.
+    assert_eq!(
+        fair.start(&player("2"), day, 2020),
+        Utc.with_ymd_and_hms(2020, 12, 1, 14, 30, 0).unwrap()
+    );
+    // Default time zone is UTC, unlock at 05:00 UTC.
+    assert_eq!(
+        fair.start(&player("3"), day, 2020),
+        Utc.with_ymd_and_hms(2020, 12, 1, 7, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn time_zone_fair_before_start() {
+    // Player 2 starts day 1 at 14:30 UTC, part 1 is solved before and part 2 an hour after.
+    let data: AocData = serde_json::from_str(
+        r#"{
+            "event": "2020",
+            "owner_id": "2",
+            "members": {
+                "2": {
+                    "id": "2",
+                    "name": "Early",
+                    "local_score": 2,
+                    "global_score": 0,
+                    "stars": 2,
+                    "last_star_ts": "1606836600",
+                    "completion_day_level": {
+                        "1": {
+                            "1": {"get_star_ts": "1606802400"},
+                            "2": {"get_star_ts": "1606836600"}
+                        }
+                    }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let points = fair().points(&data);
+    let points: Vec<String> = points[&serde_json::from_str::<PlayerId>("2").unwrap()]
+        .values()
+        .map(|points| points.to_string())
+        .collect();
+    assert_eq!(points, vec!["1440", "1380"]);
+}
+
+#[test]
+fn excluded_days_give_no_points() {
+    let data = test_data();
+    let excluded: Exclusions =
+        serde_json::from_str(r#"{"2020": [{"day": 1}, {"day": 2, "part": 2}]}"#).unwrap();
+    let strategy = Excluding {
+        strategy: OfficialScore,
+        excluded,
+    };
+    let day_1 = Day::try_new(1).unwrap();
+    let day_2 = Day::try_new(2).unwrap();
+    for (_, star_points) in strategy.points(&data) {
+        assert!(star_points.keys().all(|(day, _)| *day != day_1));
+        assert!(star_points
+            .keys()
+            .all(|(day, part)| !(*day == day_2 && part.to_string() == "2")));
+    }
+    // Stars still count
+    assert_eq!(
+        data.scores(&strategy)
+            .iter()
+            .map(|(_, score)| score.stars)
+            .collect::<Vec<_>>(),
+        data.scores(&OfficialScore)
+            .iter()
+            .map(|(_, score)| score.stars)
+            .collect::<Vec<_>>()
+    );
+    assert!(data
+        .scores_fmt(&strategy, Locale::En, &Text)
+        .starts_with("Standings (Excluded from scoring: day 1, day 2 part 2)\n"));
+}
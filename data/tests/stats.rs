@@ -0,0 +1,67 @@
+//! Test player statistics on the file `test_data.json`
+
+use aoc_data::get_local_data;
+use aoc_data::locale::Locale;
+
+#[test]
+fn player_stats_are_consistent() {
+    let data = get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing");
+    let stats = data.player_stats().unwrap();
+    assert_eq!(stats.len(), data.num_players());
+    for stats in stats.values() {
+        if let (Some(part_1), Some(part_2)) = (stats.part_1, stats.part_2) {
+            assert!(part_1.median <= part_2.median);
+        }
+        if let (Some((_, fastest)), Some((_, slowest))) = (stats.fastest_day, stats.slowest_day) {
+            assert!(fastest <= slowest);
+        }
+        assert!(stats.longest_streak <= 25);
+        assert!(stats.release_days <= 25);
+        assert!(stats
+            .fmt(Locale::En)
+            .starts_with(&format!("Stats for {}\n", stats.name)));
+    }
+}
+
+#[test]
+fn stats_table_has_a_row_per_player() {
+    let data = get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing");
+    let table = data.stats_table(Locale::En).unwrap();
+    assert_eq!(table.rows.len(), data.num_players());
+    assert!(table.rows.iter().all(|row| row.len() == table.header.len()));
+}
+
+#[test]
+fn day_stats_are_consistent() {
+    let data = get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing");
+    let days = data.day_stats().unwrap();
+    assert!(!days.is_empty());
+    for day in &days {
+        assert!(day.part_2 <= day.part_1 && day.part_1 <= data.num_players());
+        assert_eq!(day.median.is_some(), day.part_2 > 0);
+    }
+    assert!(days
+        .windows(2)
+        .all(|pair| pair[0].completion_share() <= pair[1].completion_share()));
+}
+
+#[test]
+fn solve_times_cover_all_stars() {
+    let data = get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing");
+    let rows = data.solve_times().unwrap();
+    assert_eq!(rows.len(), data.num_players());
+    let stars = |row: &aoc_data::stats::SolveTimesRow| {
+        row.days
+            .values()
+            .map(|times| 1 + times.part_2.iter().count())
+            .sum::<usize>()
+    };
+    assert!(rows
+        .windows(2)
+        .all(|pair| stars(&pair[0]) >= stars(&pair[1])));
+    for row in &rows {
+        for times in row.days.values() {
+            assert!(times.part_2.map_or(true, |part_2| times.part_1 <= part_2));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Test the text renderings on the file `test_data.json`
+
+use aoc_data::score::OfficialScore;
+use aoc_data::{get_local_data, AocData};
+
+fn test_data() -> AocData {
+    get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing")
+}
+
+/// Width of the rendering after the name column
+fn widths(text: &str) -> Vec<usize> {
+    text.lines()
+        .map(|line| line.chars().skip(21).count())
+        .collect()
+}
+
+#[test]
+fn star_bars_line_up() {
+    let data = test_data();
+    let bars = data.fmt_star_bars();
+    assert_eq!(bars.lines().count(), data.num_players());
+    let widths = widths(&bars);
+    assert!(widths.iter().all(|width| *width == widths[0]));
+}
+
+#[test]
+fn rank_sparklines_line_up() {
+    let data = test_data();
+    let sparklines = data.fmt_rank_sparklines(&OfficialScore, 5).unwrap();
+    assert_eq!(sparklines.lines().count(), 5);
+    let widths = widths(&sparklines);
+    assert!(widths.iter().all(|width| *width == widths[0]));
+    // The leader is at the top level at the end of the event.
+    assert!(sparklines.lines().next().unwrap().ends_with('\u{2588}'));
+}
+
+#[test]
+fn solver_histogram_has_all_days() {
+    let data = test_data();
+    let histogram = data.fmt_solver_histogram();
+    assert!(histogram.starts_with(" 1 "));
+    assert!(histogram.lines().count() <= 25);
+}
@@ -0,0 +1,41 @@
+//! Test head-to-head comparisons on the file `test_data.json`
+
+use aoc_data::locale::Locale;
+use aoc_data::versus::Side;
+use aoc_data::{get_local_data, AocData};
+
+fn test_data() -> AocData {
+    get_local_data("tests/data/test_data.json").expect("File: 'test_data.json' missing")
+}
+
+#[test]
+fn head_to_head_is_symmetric() {
+    let data = test_data();
+    let (jacko, nes) = (
+        data.player_id("jacko").unwrap(),
+        data.player_id("Nes").unwrap(),
+    );
+    let ab = data.head_to_head(&jacko, &nes).unwrap();
+    let ba = data.head_to_head(&nes, &jacko).unwrap();
+    let (wins_a, wins_b, ties) = ab.record();
+    assert_eq!(ba.record(), (wins_b, wins_a, ties));
+    assert_eq!(ab.local_score_gap, -ba.local_score_gap);
+    assert!(wins_a + wins_b + ties > 0);
+    for (duel_ab, duel_ba) in ab.duels.iter().zip(ba.duels.iter()) {
+        assert_eq!(duel_ab.margin(), duel_ba.margin());
+        let flipped = duel_ba.winner().map(|side| match side {
+            Side::A => Side::B,
+            Side::B => Side::A,
+        });
+        assert_eq!(duel_ab.winner(), flipped);
+    }
+    assert!(ab.fmt(Locale::En).starts_with("Jacko vs Nes: "));
+}
+
+#[test]
+fn head_to_head_unknown_player() {
+    let data = test_data();
+    let jacko = data.player_id("Jacko").unwrap();
+    let missing = serde_json::from_str("0").unwrap();
+    assert!(data.head_to_head(&jacko, &missing).is_err());
+}
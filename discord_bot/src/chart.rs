@@ -0,0 +1,184 @@
+//! Leaderboard charts, rendered as PNG
+//!
+//! Only available with the `chart` feature.
+//! Text is drawn with an embedded font, so no system fonts are needed.
+use anyhow::Result;
+use aoc_data::locale::Locale;
+use aoc_data::progression::{Metric, Series};
+use aoc_data::stats::SolveTimesRow;
+use aoc_data::time::Day;
+use chrono::{DateTime, Duration, Local};
+use plotters::prelude::*;
+use plotters::style::register_font;
+use std::sync::Once;
+
+const WIDTH: u32 = 1024;
+const HEIGHT: u32 = 640;
+/// Side of a heatmap cell, in pixels
+const CELL: i32 = 24;
+/// Width of the name column in a heatmap, in pixels
+const NAME_WIDTH: i32 = 160;
+/// Solve time with the slowest colour in a heatmap
+const HEATMAP_CAP_HOURS: i64 = 24;
+/// Font of all text, registered as "sans-serif"
+const FONT: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+
+/// Name of a player and their values in local time, one line of a chart
+type Line<'a> = (&'a str, Vec<(DateTime<Local>, u32)>);
+
+/// Register [`FONT`], before the first text is drawn
+fn register_fonts() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        register_font("sans-serif", FontStyle::Normal, FONT)
+            .unwrap_or_else(|_| panic!("Embedded font is not a valid font"))
+    });
+}
+
+/// Encode the RGB pixels in `buf` of an image of `size` as PNG
+fn encode_png(buf: &[u8], (width, height): (u32, u32)) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(buf)?;
+    Ok(png)
+}
+
+/// Render `series` as a line chart to PNG
+///
+/// Ranks are drawn with the leader at the top.
+pub fn render_png(series: &[Series], metric: Metric, locale: Locale) -> Result<Vec<u8>> {
+    register_fonts();
+    let lines: Vec<Line> = series
+        .iter()
+        .map(|series| {
+            let values = series
+                .values
+                .iter()
+                .map(|(ts, value)| ((*ts).into(), *value))
+                .collect();
+            (series.name.as_str(), values)
+        })
+        .collect();
+    let times = || {
+        lines
+            .iter()
+            .flat_map(|(_, values)| values.iter().map(|(t, _)| *t))
+    };
+    let (start, end) = match (times().min(), times().max()) {
+        (Some(start), Some(end)) => (start, end),
+        _ => (Local::now(), Local::now()),
+    };
+    let max = lines
+        .iter()
+        .flat_map(|(_, values)| values.iter().map(|(_, v)| *v))
+        .max()
+        .unwrap_or(0)
+        + 1;
+    // Flip ranks so that the leader is at the top of the chart.
+    let y = |value: u32| match metric {
+        Metric::Points => value,
+        Metric::Rank => max - value,
+    };
+    let caption = match metric {
+        Metric::Points => locale.catalog().points,
+        Metric::Rank => locale.catalog().rank,
+    };
+
+    let mut buf = vec![0; (WIDTH * HEIGHT * 3) as usize];
+    // The drawing area borrows `buf` until the end of the block.
+    {
+        let root = BitMapBackend::with_buffer(&mut buf, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(caption, ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(40)
+            .build_cartesian_2d(start..end, 0..max)?;
+        chart
+            .configure_mesh()
+            .x_label_formatter(&|t| t.format("%d/%m").to_string())
+            .y_label_formatter(&|v| match metric {
+                Metric::Points => v.to_string(),
+                Metric::Rank => (max - v).to_string(),
+            })
+            .draw()?;
+        for (idx, (name, values)) in lines.iter().enumerate() {
+            let color = Palette99::pick(idx).to_rgba();
+            // Values hold until the next change, draw them as steps.
+            let steps = values
+                .iter()
+                .zip(values.iter().skip(1))
+                .flat_map(|((t, v), (next, _))| vec![(*t, y(*v)), (*next, y(*v))])
+                .chain(values.last().map(|(_, v)| (end, y(*v))));
+            chart
+                .draw_series(LineSeries::new(steps, color.stroke_width(2)))?
+                .label(*name)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+        root.present()?;
+    }
+    encode_png(&buf, (WIDTH, HEIGHT))
+}
+
+/// Render the solve times in `rows` as a heatmap to PNG
+///
+/// Players are rows and days are columns.
+/// The upper half of a cell is part 1 and the lower half is part 2,
+/// coloured from green to red by the time from unlock. Missing stars are grey.
+pub fn render_heatmap_png(rows: &[SolveTimesRow]) -> Result<Vec<u8>> {
+    register_fonts();
+    let width = (NAME_WIDTH + 25 * CELL + 10) as u32;
+    let height = (CELL * (rows.len() as i32 + 1) + 10) as u32;
+    let mut buf = vec![0; (width * height * 3) as usize];
+    // The drawing area borrows `buf` until the end of the block.
+    {
+        let root = BitMapBackend::with_buffer(&mut buf, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let font = ("sans-serif", 14).into_font();
+        for (col, day) in Day::all().enumerate() {
+            let x = NAME_WIDTH + col as i32 * CELL;
+            root.draw(&Text::new(day.to_string(), (x + 4, 4), font.clone()))?;
+        }
+        for (idx, row) in rows.iter().enumerate() {
+            let y = CELL * (idx as i32 + 1);
+            root.draw(&Text::new(row.name.clone(), (4, y + 4), font.clone()))?;
+            for (col, day) in Day::all().enumerate() {
+                let x = NAME_WIDTH + col as i32 * CELL;
+                let times = row.days.get(&day);
+                let part_1 = times.map(|times| times.part_1);
+                let part_2 = times.and_then(|times| times.part_2);
+                let half = CELL / 2;
+                root.draw(&Rectangle::new(
+                    [(x, y), (x + CELL - 1, y + half)],
+                    heat_colour(part_1).filled(),
+                ))?;
+                root.draw(&Rectangle::new(
+                    [(x, y + half), (x + CELL - 1, y + CELL - 1)],
+                    heat_colour(part_2).filled(),
+                ))?;
+            }
+        }
+        root.present()?;
+    }
+    encode_png(&buf, (width, height))
+}
+
+/// Green for a fast solve, red at or after the cap and grey for a missing star
+fn heat_colour(time: Option<Duration>) -> RGBColor {
+    match time {
+        Some(time) => {
+            let cap = Duration::hours(HEATMAP_CAP_HOURS).num_seconds() as f64;
+            let share = (time.num_seconds() as f64 / cap).clamp(0.0, 1.0);
+            RGBColor((255.0 * share) as u8, (200.0 * (1.0 - share)) as u8, 0)
+        }
+        None => RGBColor(200, 200, 200),
+    }
+}
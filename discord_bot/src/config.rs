@@ -1,37 +1,433 @@
-use anyhow::Result;
-use serde::{de, Deserialize, Deserializer, Serialize};
-use serde_json::Value;
+use anyhow::{Context, Result};
+use aoc_data::locale::Locale;
+use aoc_data::render::Format;
+use aoc_data::score::{Excluding, Exclusions, Scoring, TimeZoneFair};
+use aoc_data::time::{de_opt_hh_mm, TimeDisplay};
+use aoc_data::AocError;
+use chrono::NaiveTime;
+use serde::{de, de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 use serenity::model::id::{ApplicationId, ChannelId};
-use std::fs::File;
-use std::io::Read;
+use std::collections::HashMap;
+use std::env::{self, VarError};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::watch;
 use tokio::time::Duration;
 
+/// Shortest `api_delay`, since AoC asks for at most one request per 15 minutes
+pub const MIN_API_DELAY: Duration = Duration::from_secs(15 * 60);
+/// Shortest plausible AoC session cookie, in hexadecimal digits
+const MIN_COOKIE_LEN: usize = 64;
+
+/// Prefix of the environment variables overriding config fields, e.g. `AOC_BOT_TOKEN`
+const ENV_PREFIX: &str = "AOC_BOT_";
+/// Suffix of a config key or environment variable naming a file with the value of a field,
+/// e.g. `token_file` or `AOC_BOT_TOKEN_FILE`
+const FILE_SUFFIX: &str = "_file";
+
+/// How a field is given in an environment variable or a file
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum FieldKind {
+    /// Taken as is
+    Text,
+    /// Parsed as JSON, e.g. `900` or `{"kind": "stars"}`
+    Json,
+    /// Parsed as JSON, e.g. `900`, or taken as is if it is not JSON, e.g. `15m`
+    JsonOrText,
+}
+
+/// All fields of [`AocBotConfig`]
+const FIELDS: [(&str, FieldKind); 14] = [
+    ("api_delay", FieldKind::JsonOrText),
+    ("token", FieldKind::Text),
+    ("application_id", FieldKind::Json),
+    ("channel_id", FieldKind::Json),
+    ("aoc_cookie", FieldKind::Text),
+    ("digest_time", FieldKind::Text),
+    ("digest_top", FieldKind::Json),
+    ("scoring", FieldKind::Json),
+    ("time_zone_fair", FieldKind::Json),
+    ("excluded", FieldKind::Json),
+    ("locale", FieldKind::Json),
+    ("time_display", FieldKind::Json),
+    ("format", FieldKind::Json),
+    ("aoc_names", FieldKind::Json),
+];
+
+/// A field given on the command line as `field=value`, e.g. `--set digest_top=5`
+///
+/// The value is given in the same way as in an environment variable.
+#[derive(Clone, Debug)]
+pub struct FieldOverride {
+    field: &'static str,
+    val: Value,
+}
+
+impl FromStr for FieldOverride {
+    type Err = AocError;
+
+    fn from_str(arg: &str) -> Result<Self, AocError> {
+        let invalid = |reason: &str| AocError::Param {
+            param: "--set".to_string(),
+            val: arg.to_string(),
+            reason: reason.to_string(),
+        };
+        let (field, val) = arg
+            .split_once('=')
+            .ok_or_else(|| invalid("Expected field=value"))?;
+        let (field, kind) = FIELDS
+            .iter()
+            .find(|(name, _)| *name == field.trim())
+            .copied()
+            .ok_or_else(|| invalid("Unknown field"))?;
+        Ok(FieldOverride {
+            field,
+            val: field_value(field, kind, val)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AocBotConfig {
+    /// Time between requests to the AoC API, e.g. "15m", or an integer number of seconds
     #[serde(deserialize_with = "de_tokio_duration")]
-    /// Duration in seconds
     pub api_delay: Duration,
     pub token: String,
     pub application_id: ApplicationId,
     pub channel_id: ChannelId,
     pub aoc_cookie: String,
+    /// Local time of the daily digest, e.g. "23:00". No digest if missing.
+    #[serde(default, deserialize_with = "de_opt_hh_mm")]
+    pub digest_time: Option<NaiveTime>,
+    /// Number of players in the standings of the daily digest
+    #[serde(default = "default_digest_top")]
+    pub digest_top: usize,
+    /// Default scoring strategy for standings and rank changes
+    #[serde(default)]
+    pub scoring: Scoring,
+    /// Time zone fair scoring, shown as a separate ranking. Not shown if missing.
+    #[serde(default)]
+    pub time_zone_fair: Option<TimeZoneFair>,
+    /// Days or parts of each event that give no points, e.g. `{"2020": [{"day": 1}]}`
+    #[serde(default)]
+    pub excluded: Exclusions,
+    /// Language of the bot output, e.g. `{"default": "en", "channels": {"2": "sv"}}`
+    #[serde(default)]
+    pub locale: PerChannel<Locale>,
+    /// Time zone and format of timestamps,
+    /// e.g. `{"default": {"time_zone": "Europe/Stockholm", "format": "clock"}}`
+    #[serde(default)]
+    pub time_display: PerChannel<TimeDisplay>,
+    /// Format of standings and news tables, e.g. `{"default": "text", "channels": {"2": "markdown"}}`
+    #[serde(default)]
+    pub format: PerChannel<Format>,
+    /// AoC player names of Discord users, by user id, e.g. `{"123456": "Jacko"}`.
+    /// Used for players mentioned in commands.
+    #[serde(default)]
+    pub aoc_names: HashMap<u64, String>,
+}
+
+/// Config of the running bot, replaced when the config file is reloaded
+pub type LiveConfig = watch::Receiver<Arc<AocBotConfig>>;
+
+/// Setting for each channel, with a default for channels not listed
+///
+/// A channel setting given as an object is merged over the default field by field,
+/// e.g. `{"2": {"format": "discord"}}` keeps the default time zone.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PerChannel<T> {
+    pub default: T,
+    pub channels: HashMap<u64, T>,
+}
+
+impl<'de, T: DeserializeOwned + Default> Deserialize<'de> for PerChannel<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            #[serde(default)]
+            default: Option<Value>,
+            #[serde(default)]
+            channels: HashMap<u64, Value>,
+        }
+        let Fields { default, channels } = Fields::deserialize(deserializer)?;
+        let parse = |val: Value| T::deserialize(val).map_err(de::Error::custom);
+        let channels = channels
+            .into_iter()
+            .map(|(id, val)| {
+                let val = match (&default, val) {
+                    (Some(Value::Object(default)), Value::Object(fields)) => {
+                        let mut merged = default.clone();
+                        merged.extend(fields);
+                        Value::Object(merged)
+                    }
+                    (_, val) => val,
+                };
+                Ok((id, parse(val)?))
+            })
+            .collect::<Result<_, D::Error>>()?;
+        let default = default.map(parse).transpose()?.unwrap_or_default();
+        Ok(PerChannel { default, channels })
+    }
+}
+
+impl<T: Copy> PerChannel<T> {
+    pub fn get(&self, channel_id: ChannelId) -> T {
+        self.channels
+            .get(&channel_id.0)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+fn default_digest_top() -> usize {
+    10
+}
+
+/// Value of the environment variable `env_var`, `None` if it is not set
+fn env_value(
+    env: impl Fn(&str) -> Result<String, VarError>,
+    env_var: &str,
+) -> Result<Option<String>, AocError> {
+    match env(env_var) {
+        Ok(val) => Ok(Some(val)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(source) => Err(AocError::Env {
+            env_var: env_var.to_string(),
+            source,
+        }),
+    }
+}
+
+/// Value of a field of `kind`, given as text in `param`
+fn field_value(param: &str, kind: FieldKind, val: &str) -> Result<Value, AocError> {
+    match kind {
+        FieldKind::Text => Ok(Value::String(val.to_string())),
+        FieldKind::Json => serde_json::from_str(val).map_err(|err| AocError::Param {
+            param: param.to_string(),
+            val: val.to_string(),
+            reason: format!("Expected JSON: {}", err),
+        }),
+        FieldKind::JsonOrText => {
+            Ok(serde_json::from_str(val).unwrap_or_else(|_| Value::String(val.to_string())))
+        }
+    }
+}
+
+/// Value of `field`, read from `file`
+///
+/// Surrounding whitespace, such as a trailing newline, is not part of the value.
+fn read_field_file(field: &str, kind: FieldKind, file: &str) -> Result<Value> {
+    let val =
+        fs::read_to_string(file).with_context(|| format!("Reading {} from {}", field, file))?;
+    let val = field_value(field, kind, val.trim())
+        .with_context(|| format!("Reading {} from {}", field, file))?;
+    Ok(val)
 }
 
 impl AocBotConfig {
-    pub fn from_config(config: &str) -> Result<AocBotConfig> {
-        let mut file = File::open(config)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        Ok(serde_json::from_str(&contents)?)
+    /// Load the config file `config`, with overrides from the environment and the command line
+    ///
+    /// The file is TOML if its extension is `.toml`, otherwise JSON.
+    ///
+    /// The value of a field, e.g. `token`, is taken from the first of:
+    ///
+    /// - the `overrides` given on the command line, e.g. `--set token=...`
+    /// - the file named by the environment variable `AOC_BOT_TOKEN_FILE`
+    /// - the environment variable `AOC_BOT_TOKEN`
+    /// - the file named by the key `token_file` in the config file
+    /// - the key `token` in the config file
+    ///
+    /// Files are meant for secrets, e.g. in Docker secrets or systemd credential directories.
+    pub fn from_config(config: &str, overrides: &[FieldOverride]) -> Result<AocBotConfig> {
+        let contents =
+            fs::read_to_string(config).with_context(|| format!("Reading config {}", config))?;
+        let fields: Map<String, Value> = match Path::new(config).extension() {
+            Some(ext) if ext == "toml" => {
+                toml::from_str(&contents).with_context(|| format!("Parsing config {}", config))?
+            }
+            _ => serde_json::from_str(&contents)
+                .with_context(|| format!("Parsing config {}", config))?,
+        };
+        Self::from_layers(fields, |var| env::var(var), overrides)
+    }
+
+    /// Apply the secret files, the environment variables given by `env` and the command line
+    /// `overrides` to the config `fields`, then parse the config
+    fn from_layers(
+        mut fields: Map<String, Value>,
+        env: impl Fn(&str) -> Result<String, VarError>,
+        overrides: &[FieldOverride],
+    ) -> Result<AocBotConfig> {
+        for (field, kind) in FIELDS.iter() {
+            if let Some(file) = fields.remove(&format!("{}{}", field, FILE_SUFFIX)) {
+                let file = file.as_str().ok_or_else(|| AocError::Param {
+                    param: format!("{}{}", field, FILE_SUFFIX),
+                    val: file.to_string(),
+                    reason: "Expected a file name".to_string(),
+                })?;
+                fields.insert(field.to_string(), read_field_file(field, *kind, file)?);
+            }
+            let env_var = format!("{}{}", ENV_PREFIX, field.to_uppercase());
+            if let Some(val) = env_value(&env, &env_var)? {
+                fields.insert(field.to_string(), field_value(&env_var, *kind, &val)?);
+            }
+            let env_var = format!("{}{}", env_var, FILE_SUFFIX.to_uppercase());
+            if let Some(file) = env_value(&env, &env_var)? {
+                fields.insert(field.to_string(), read_field_file(field, *kind, &file)?);
+            }
+        }
+        for FieldOverride { field, val } in overrides {
+            fields.insert(field.to_string(), val.clone());
+        }
+        serde_path_to_error::deserialize(Value::Object(fields)).map_err(|err| {
+            let param = err.path().to_string();
+            AocError::Param {
+                param,
+                val: String::new(),
+                reason: err.into_inner().to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Problems with the values of the fields, empty if there are none
+    ///
+    /// Secrets are not included in the problems.
+    pub fn problems(&self) -> Vec<AocError> {
+        let mut problems = Vec::new();
+        let mut problem = |param: &str, val: String, reason: &str| {
+            problems.push(AocError::Param {
+                param: param.to_string(),
+                val,
+                reason: reason.to_string(),
+            })
+        };
+        if self.api_delay < MIN_API_DELAY {
+            problem(
+                "api_delay",
+                format!("{}s", self.api_delay.as_secs()),
+                "AoC rate limits polling more often than every 15 minutes",
+            );
+        }
+        if self.token.is_empty() || self.token.contains(char::is_whitespace) {
+            problem("token", String::new(), "Expected a Discord bot token");
+        }
+        if self.aoc_cookie.starts_with("session=") {
+            problem(
+                "aoc_cookie",
+                String::new(),
+                "Expected the value of the session cookie, without `session=`",
+            );
+        } else if self.aoc_cookie.len() < MIN_COOKIE_LEN
+            || !self.aoc_cookie.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            problem(
+                "aoc_cookie",
+                String::new(),
+                "Expected the hexadecimal value of the AoC session cookie",
+            );
+        }
+        if self.digest_top == 0 {
+            problem(
+                "digest_top",
+                "0".to_string(),
+                "Expected at least one player",
+            );
+        }
+        match self.scoring {
+            Scoring::TimeSinceUnlock { cap_hours: 0 } | Scoring::Part2Delta { cap_hours: 0 } => {
+                problem(
+                    "scoring.cap_hours",
+                    "0".to_string(),
+                    "Expected a positive cap",
+                )
+            }
+            _ => {}
+        }
+        if let Some(fair) = &self.time_zone_fair {
+            if fair.cap_hours == 0 {
+                problem(
+                    "time_zone_fair.cap_hours",
+                    "0".to_string(),
+                    "Expected a positive cap",
+                );
+            }
+        }
+        problems
+    }
+
+    /// All channels in the config, with the field they are given in
+    pub fn channels(&self) -> Vec<(&'static str, ChannelId)> {
+        let mut channels = vec![("channel_id", self.channel_id)];
+        let overrides = self
+            .locale
+            .channels
+            .keys()
+            .map(|id| ("locale.channels", ChannelId(*id)))
+            .chain(
+                self.time_display
+                    .channels
+                    .keys()
+                    .map(|id| ("time_display.channels", ChannelId(*id))),
+            )
+            .chain(
+                self.format
+                    .channels
+                    .keys()
+                    .map(|id| ("format.channels", ChannelId(*id))),
+            );
+        for (field, channel_id) in overrides {
+            if channels.iter().all(|(_, known)| *known != channel_id) {
+                channels.push((field, channel_id));
+            }
+        }
+        channels
+    }
+
+    /// `new` as the replacement of the running config `self`
+    ///
+    /// The token and the application id are tied to the Discord connection and keep their
+    /// current values. The names of those that `new` would change are returned.
+    pub fn reload(&self, mut new: AocBotConfig) -> (AocBotConfig, Vec<&'static str>) {
+        let mut kept = Vec::new();
+        if new.token != self.token {
+            new.token = self.token.clone();
+            kept.push("token");
+        }
+        if new.application_id != self.application_id {
+            new.application_id = self.application_id;
+            kept.push("application_id");
+        }
+        (new, kept)
+    }
+
+    /// The default scoring strategy, with the excluded days
+    pub fn scoring(&self) -> Excluding<Scoring> {
+        Excluding {
+            strategy: self.scoring,
+            excluded: self.excluded.clone(),
+        }
+    }
+
+    /// The time zone fair scoring strategy, with the excluded days
+    pub fn time_zone_fair(&self) -> Option<Excluding<TimeZoneFair>> {
+        self.time_zone_fair.clone().map(|strategy| Excluding {
+            strategy,
+            excluded: self.excluded.clone(),
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
     use std::fs::File;
-    use std::io::Read;
+    use std::io::{Read, Write};
 
     use super::*;
+    use aoc_data::time::TimeFormat;
 
     #[test]
     fn parse_sample_config() {
@@ -43,6 +439,188 @@ mod test {
         assert_eq!(config.token, String::from("secret-token"));
         assert_eq!(config.application_id, 1);
         assert_eq!(config.channel_id, 2);
+        assert_eq!(config.digest_time, NaiveTime::from_hms_opt(23, 0, 0));
+        assert_eq!(config.digest_top, 10);
+        assert_eq!(config.scoring, Scoring::LocalScore);
+        let fair = config
+            .time_zone_fair
+            .expect("Missing time zone fair scoring");
+        assert_eq!(fair.players.len(), 1);
+        assert_eq!(config.excluded["2020"].len(), 1);
+        assert_eq!(config.locale.get(ChannelId(2)), Locale::Sv);
+        assert_eq!(config.locale.get(ChannelId(3)), Locale::En);
+        let times = config.time_display.get(ChannelId(2));
+        assert_eq!(times.time_zone, Some("Europe/Stockholm".parse().unwrap()));
+        assert_eq!(times.format, TimeFormat::Clock);
+        assert_eq!(config.format.get(ChannelId(2)), Format::Markdown);
+        assert_eq!(config.format.get(ChannelId(3)), Format::Text);
+        assert_eq!(config.aoc_names[&123456], "Jacko");
+    }
+
+    fn sample_fields() -> Map<String, Value> {
+        let contents = fs::read_to_string("../sample_config.json").expect("File read fail");
+        serde_json::from_str(&contents).expect("Parse fail")
+    }
+
+    /// Sample config with plausible secrets
+    fn valid_config() -> AocBotConfig {
+        AocBotConfig::from_layers(sample_fields(), env(&[]), &[]).expect("Load fail")
+    }
+
+    #[test]
+    fn sample_configs_are_valid() {
+        for sample in ["../sample_config.json", "../sample_config.toml"] {
+            let config = AocBotConfig::from_config(sample, &[]).expect("Load fail");
+            let problems: Vec<String> = config.problems().iter().map(|p| p.to_string()).collect();
+            assert!(problems.is_empty(), "{}: {:?}", sample, problems);
+        }
+    }
+
+    #[test]
+    fn parse_toml_config() {
+        let json = AocBotConfig::from_config("../sample_config.json", &[]).expect("JSON fail");
+        let toml = AocBotConfig::from_config("../sample_config.toml", &[]).expect("TOML fail");
+        assert_eq!(toml.api_delay, Duration::from_secs(15 * 60));
+        assert_eq!(json.channel_id, toml.channel_id);
+        assert_eq!(json.scoring, toml.scoring);
+        assert_eq!(json.excluded, toml.excluded);
+        assert_eq!(json.locale.get(ChannelId(2)), toml.locale.get(ChannelId(2)));
+        assert_eq!(json.format.get(ChannelId(2)), toml.format.get(ChannelId(2)));
+        assert_eq!(json.aoc_names, toml.aoc_names);
+    }
+
+    #[test]
+    fn validation() {
+        assert!(valid_config().problems().is_empty());
+        let mut config = valid_config();
+        config.api_delay = Duration::from_secs(60);
+        config.aoc_cookie = format!("session={}", config.aoc_cookie);
+        config.digest_top = 0;
+        let problems: Vec<String> = config.problems().iter().map(|p| p.to_string()).collect();
+        assert_eq!(problems.len(), 3);
+        assert!(problems[0].contains("api_delay"));
+        assert!(problems[1].contains("aoc_cookie"));
+        assert!(problems[2].contains("digest_top"));
+        assert_eq!(
+            config
+                .channels()
+                .iter()
+                .map(|(_, id)| *id)
+                .collect::<Vec<_>>(),
+            vec![ChannelId(2)]
+        );
+    }
+
+    #[test]
+    fn channel_settings_merge_over_the_default() {
+        let times: PerChannel<TimeDisplay> = serde_json::from_str(
+            r#"{
+                "default": {"time_zone": "Europe/Stockholm", "format": "clock"},
+                "channels": {"2": {"format": "discord"}}
+            }"#,
+        )
+        .expect("Parse fail");
+        let merged = times.get(ChannelId(2));
+        assert_eq!(merged.time_zone, Some("Europe/Stockholm".parse().unwrap()));
+        assert_eq!(merged.format, TimeFormat::Discord);
+        assert_eq!(times.get(ChannelId(3)).format, TimeFormat::Clock);
+        let locales: PerChannel<Locale> =
+            serde_json::from_str(r#"{"channels": {"2": "sv"}}"#).expect("Parse fail");
+        assert_eq!(locales.get(ChannelId(2)), Locale::Sv);
+        assert_eq!(locales.get(ChannelId(3)), Locale::En);
+    }
+
+    #[test]
+    fn reload_keeps_the_connection() {
+        let running = valid_config();
+        let mut new = valid_config();
+        new.token = "new-token".to_string();
+        new.api_delay = Duration::from_secs(20 * 60);
+        let (reloaded, kept) = running.reload(new);
+        assert_eq!(kept, vec!["token"]);
+        assert_eq!(reloaded.token, running.token);
+        assert_eq!(reloaded.api_delay, Duration::from_secs(20 * 60));
+    }
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Result<String, VarError> + 'a {
+        move |var| {
+            vars.iter()
+                .find(|(name, _)| *name == var)
+                .map(|(_, val)| val.to_string())
+                .ok_or(VarError::NotPresent)
+        }
+    }
+
+    #[test]
+    fn env_overrides() {
+        let vars = [
+            ("AOC_BOT_TOKEN", "env-token"),
+            ("AOC_BOT_API_DELAY", "1800"),
+            ("AOC_BOT_SCORING", r#"{"kind": "stars"}"#),
+        ];
+        let config =
+            AocBotConfig::from_layers(sample_fields(), env(&vars), &[]).expect("Load fail");
+        assert_eq!(config.token, "env-token");
+        assert_eq!(config.api_delay, Duration::from_secs(1800));
+        assert_eq!(config.scoring, Scoring::Stars);
+        assert_eq!(config.aoc_cookie, "0".repeat(128));
+    }
+
+    #[test]
+    fn command_line_overrides() {
+        let vars = [("AOC_BOT_DIGEST_TOP", "3")];
+        let overrides: Vec<FieldOverride> = ["digest_top=5", r#"scoring={"kind": "stars"}"#]
+            .iter()
+            .map(|arg| arg.parse().expect("Override parse fail"))
+            .collect();
+        let config =
+            AocBotConfig::from_layers(sample_fields(), env(&vars), &overrides).expect("Load fail");
+        assert_eq!(config.digest_top, 5);
+        assert_eq!(config.scoring, Scoring::Stars);
+        assert!("nope=1".parse::<FieldOverride>().is_err());
+        assert!("digest_top".parse::<FieldOverride>().is_err());
+    }
+
+    #[test]
+    fn secret_files() {
+        let dir = env::temp_dir();
+        let write = |name: &str, contents: &str| {
+            let path = dir.join(name);
+            let mut file = File::create(&path).expect("File create fail");
+            file.write_all(contents.as_bytes())
+                .expect("File write fail");
+            path.to_string_lossy().to_string()
+        };
+        let token_file = write("aoc_bot_test_token", "file-token\n");
+        let cookie_file = write("aoc_bot_test_cookie", "file-cookie\n");
+        let mut fields = sample_fields();
+        fields.insert("token_file".to_string(), Value::String(token_file));
+        let vars = [("AOC_BOT_AOC_COOKIE_FILE", cookie_file.as_str())];
+        let config = AocBotConfig::from_layers(fields, env(&vars), &[]).expect("Load fail");
+        assert_eq!(config.token, "file-token");
+        assert_eq!(config.aoc_cookie, "file-cookie");
+    }
+
+    #[test]
+    fn errors_name_the_field() {
+        let vars = [("AOC_BOT_CHANNEL_ID", r#""general""#)];
+        let err = AocBotConfig::from_layers(sample_fields(), env(&vars), &[]).unwrap_err();
+        assert!(err.to_string().contains("channel_id"));
+        let vars = [("AOC_BOT_DIGEST_TOP", r#""ten""#)];
+        let err = AocBotConfig::from_layers(sample_fields(), env(&vars), &[]).unwrap_err();
+        assert!(err.to_string().contains("digest_top"));
+        let vars = [("AOC_BOT_DIGEST_TOP", "ten")];
+        let err = AocBotConfig::from_layers(sample_fields(), env(&vars), &[]).unwrap_err();
+        assert!(err.to_string().contains("AOC_BOT_DIGEST_TOP"));
+        assert!("digest_top=ten".parse::<FieldOverride>().is_err());
+        let vars = [("AOC_BOT_API_DELAY", "20m")];
+        let config =
+            AocBotConfig::from_layers(sample_fields(), env(&vars), &[]).expect("Load fail");
+        assert_eq!(config.api_delay, Duration::from_secs(20 * 60));
+        let mut fields = sample_fields();
+        fields.remove("token");
+        let err = AocBotConfig::from_layers(fields, env(&[]), &[]).unwrap_err();
+        assert!(err.to_string().contains("token"));
     }
 }
 
@@ -50,12 +628,15 @@ mod test {
 ///
 /// The same integer can create many durations (from_secs, from_millis, et c.).
 /// Need custom deserialiser to select one (here: seconds.).
+/// Strings are human-readable durations, e.g. "15m" or "1h 30m".
 fn de_tokio_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
     match Value::deserialize(deserializer)? {
         Value::Number(ts) => match ts.as_u64() {
             Some(ts) => Ok(Duration::from_secs(ts)),
             None => Err(de::Error::custom("u64 ts parsing")),
         },
+        Value::String(duration) => humantime::parse_duration(duration.trim())
+            .map_err(|err| de::Error::custom(format!("duration parsing: {}", err))),
         _ => Err(de::Error::custom("wrong type")),
     }
 }
@@ -1,6 +1,16 @@
-use crate::config::AocBotConfig;
+use crate::config::{AocBotConfig, LiveConfig};
+use crate::metrics::Metrics;
 use anyhow::Result;
-use aoc_data::{get_aoc_data, get_local_data, STAR_SYMBOL};
+use aoc_data::diff::Diff;
+use aoc_data::digest::Digest;
+use aoc_data::locale::{fill, Locale};
+use aoc_data::progression::Metric;
+use aoc_data::render::{Format, Render, Table};
+use aoc_data::score::{Excluding, Scoring, TimeZoneFair};
+use aoc_data::stats::fmt_hardest;
+use aoc_data::time::Day;
+use aoc_data::{get_aoc_data, get_local_data, AocData, AocError, STAR_SYMBOL};
+use chrono::{Duration as ChronoDuration, Local, Utc};
 use serenity::{
     async_trait,
     http::Http,
@@ -8,77 +18,275 @@ use serenity::{
         channel::Message,
         gateway::Ready,
         id::{ApplicationId, ChannelId},
+        user::User,
     },
     prelude::*,
 };
-use tokio::time::{interval, Duration};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::sync::Arc;
+use tokio::time::{interval, interval_at, sleep, Instant};
+use tracing::{debug, error, field, info, info_span, instrument, warn, Instrument, Span};
+#[cfg(feature = "chart")]
+pub mod chart;
 pub mod config;
+pub mod logging;
+pub mod metrics;
+pub mod reload;
 
 const STORED_DATA_FILE: &str = "latest.json";
+const THREADS_FILE: &str = "threads.json";
+const DIGEST_DATA_FILE: &str = "digest.json";
 const SCORE_CMD: &str = "?score";
+const TITLE_CMD: &str = "title";
+const STATS_CMD: &str = "stats";
+const HARDEST_CMD: &str = "hardest";
+const VERSUS_CMD: &str = "versus";
+const CHART_CMD: &str = "chart";
+const HEATMAP_CMD: &str = "heatmap";
+const HISTOGRAM_CMD: &str = "histogram";
+/// Number of players in a chart
+const CHART_TOP: usize = 10;
+/// File name of chart attachments
+#[cfg(feature = "chart")]
+const CHART_NAME: &str = "chart.png";
+/// Number of days listed by `HARDEST_CMD`
+const HARDEST_DAYS: usize = 5;
 
+/// Discussion thread for each day, stored in `THREADS_FILE`.
+type DayThreads = BTreeMap<Day, ChannelId>;
+
+/// Responder and updater following the running config `config`, recording to `metrics`
 pub async fn try_responder_client_and_updater_from_config(
-    config: AocBotConfig,
+    config: LiveConfig,
+    metrics: Arc<Metrics>,
 ) -> Result<(Client, Updater)> {
-    let responder = Client::builder(&config.token)
-        .application_id(config.application_id.into())
-        .event_handler(Responder)
+    let (token, application_id) = {
+        let config = config.borrow();
+        (config.token.clone(), config.application_id)
+    };
+    let responder = Client::builder(&token)
+        .application_id(application_id.into())
+        .event_handler(Responder {
+            config: config.clone(),
+            metrics: metrics.clone(),
+        })
         .await
         .expect("Err creating client");
-    let updater = Updater::try_from_config(config)?;
+    let updater = Updater::try_new(token, application_id, config, metrics)?;
     Ok((responder, updater))
 }
 
+/// Channels in `config` that the bot cannot see, e.g. unknown ids or missing permissions
+pub async fn channel_problems(config: &AocBotConfig) -> Vec<AocError> {
+    let http = Http::new_with_token_application_id(&config.token, u64::from(config.application_id));
+    let mut problems = Vec::new();
+    for (field, channel_id) in config.channels() {
+        if let Err(err) = http.get_channel(channel_id.0).await {
+            problems.push(AocError::Param {
+                param: field.to_string(),
+                val: channel_id.to_string(),
+                reason: format!("Channel not accessible: {}", err),
+            });
+        }
+    }
+    problems
+}
+
+/// Bot sub-part publishing updates, digests and day threads
+///
+/// Settings are read from the running config when used, so a reloaded config applies to the
+/// next update. The token and the application id are fixed at start.
 pub struct Updater {
-    api_delay: Duration,
     token: String,
     application_id: ApplicationId,
-    channel_id: ChannelId,
-    aoc_cookie: String,
+    config: LiveConfig,
+    metrics: Arc<Metrics>,
 }
 
 impl Updater {
     pub fn try_new(
-        api_delay: Duration,
         token: String,
         application_id: ApplicationId,
-        channel_id: ChannelId,
-        aoc_cookie: String,
+        config: LiveConfig,
+        metrics: Arc<Metrics>,
     ) -> Result<Self> {
         Ok(Self {
-            api_delay,
             token,
             application_id,
-            channel_id,
-            aoc_cookie,
+            config,
+            metrics,
         })
     }
 
-    pub fn try_from_config(config: AocBotConfig) -> Result<Self> {
-        Self::try_new(
-            config.api_delay,
-            config.token,
-            config.application_id,
-            config.channel_id,
-            config.aoc_cookie,
-        )
+    /// Snapshot of the running config
+    fn config(&self) -> Arc<AocBotConfig> {
+        self.config.borrow().clone()
     }
 
     /// Respond with current score
-    async fn publish_score(channel_id: &ChannelId, ctx: &Context) -> Result<Message> {
+    async fn publish_score(
+        channel_id: &ChannelId,
+        metrics: &Metrics,
+        ctx: &Context,
+        scoring: &Excluding<Scoring>,
+        time_zone_fair: Option<&Excluding<TimeZoneFair>>,
+        locale: Locale,
+        format: Format,
+    ) -> Result<Message> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        let msg = channel_id
+            .say(
+                &ctx.http,
+                &standings(&aoc_data, scoring, time_zone_fair, locale, format),
+            )
+            .await?;
+        metrics.message_sent();
+        Ok(msg)
+    }
+
+    /// Respond with the statistics of the player called `name`
+    async fn publish_stats(
+        channel_id: &ChannelId,
+        metrics: &Metrics,
+        ctx: &Context,
+        name: &str,
+        locale: Locale,
+    ) -> Result<Message> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        let stats = match aoc_data.player_id(name) {
+            Some(id) => aoc_data.player_stats()?.remove(&id),
+            None => None,
+        };
+        let reply = match stats {
+            Some(stats) => stats.fmt(locale),
+            None => fill(locale.catalog().no_player, &[&name]),
+        };
+        let msg = channel_id.say(&ctx.http, reply).await?;
+        metrics.message_sent();
+        Ok(msg)
+    }
+
+    /// Respond with the hardest days so far
+    async fn publish_hardest(
+        channel_id: &ChannelId,
+        metrics: &Metrics,
+        ctx: &Context,
+        locale: Locale,
+    ) -> Result<Message> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        let days = aoc_data.day_stats()?;
+        let msg = channel_id
+            .say(&ctx.http, fmt_hardest(&days, HARDEST_DAYS, locale))
+            .await?;
+        metrics.message_sent();
+        Ok(msg)
+    }
+
+    /// Respond with a head-to-head comparison of the players called `a` and `b`
+    async fn publish_versus(
+        channel_id: &ChannelId,
+        metrics: &Metrics,
+        ctx: &Context,
+        a: &str,
+        b: &str,
+        locale: Locale,
+    ) -> Result<Message> {
         let aoc_data = get_local_data(STORED_DATA_FILE)?;
-        let msg = channel_id.say(&ctx.http, &aoc_data.scores_fmt()).await?;
+        let reply = match (aoc_data.player_id(a), aoc_data.player_id(b)) {
+            (Some(a), Some(b)) => aoc_data.head_to_head(&a, &b)?.fmt(locale),
+            (None, _) => fill(locale.catalog().no_player, &[&a]),
+            (_, None) => fill(locale.catalog().no_player, &[&b]),
+        };
+        let msg = channel_id.say(&ctx.http, reply).await?;
+        metrics.message_sent();
         Ok(msg)
     }
 
+    /// Respond with a progression chart of `metric` for the top players
+    #[cfg(feature = "chart")]
+    async fn publish_chart(
+        channel_id: &ChannelId,
+        metrics: &Metrics,
+        http: &Http,
+        scoring: &Excluding<Scoring>,
+        metric: Metric,
+        locale: Locale,
+    ) -> Result<()> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        let series = aoc_data.progression(scoring, metric, CHART_TOP);
+        let png = chart::render_png(&series, metric, locale)?;
+        channel_id
+            .send_message(http, |msg| msg.add_file((png.as_slice(), CHART_NAME)))
+            .await?;
+        metrics.message_sent();
+        Ok(())
+    }
+
+    /// Without the `chart` feature, respond with rank sparklines instead
+    #[cfg(not(feature = "chart"))]
+    async fn publish_chart(
+        channel_id: &ChannelId,
+        metrics: &Metrics,
+        http: &Http,
+        scoring: &Excluding<Scoring>,
+        _metric: Metric,
+        _locale: Locale,
+    ) -> Result<()> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        let sparklines = aoc_data.fmt_rank_sparklines(scoring, CHART_TOP)?;
+        channel_id.say(http, code_block(&sparklines)).await?;
+        metrics.message_sent();
+        Ok(())
+    }
+
+    /// Respond with a heatmap of all players' solve times
+    #[cfg(feature = "chart")]
+    async fn publish_heatmap(channel_id: &ChannelId, metrics: &Metrics, http: &Http) -> Result<()> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        let png = chart::render_heatmap_png(&aoc_data.solve_times()?)?;
+        channel_id
+            .send_message(http, |msg| msg.add_file((png.as_slice(), CHART_NAME)))
+            .await?;
+        metrics.message_sent();
+        Ok(())
+    }
+
+    /// Without the `chart` feature, respond with star bars instead
+    #[cfg(not(feature = "chart"))]
+    async fn publish_heatmap(channel_id: &ChannelId, metrics: &Metrics, http: &Http) -> Result<()> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        channel_id
+            .say(http, code_block(&aoc_data.fmt_star_bars()))
+            .await?;
+        metrics.message_sent();
+        Ok(())
+    }
+
+    /// Respond with a histogram of the solvers for each day
+    async fn publish_histogram(
+        channel_id: &ChannelId,
+        metrics: &Metrics,
+        http: &Http,
+    ) -> Result<()> {
+        let aoc_data = get_local_data(STORED_DATA_FILE)?;
+        channel_id
+            .say(http, code_block(&aoc_data.fmt_solver_histogram()))
+            .await?;
+        metrics.message_sent();
+        Ok(())
+    }
+
     /// Check for and publish update
     async fn test_update(&self, http: &Http) -> Result<()> {
-        println!("Checking for updates");
-        self.channel_id.say(http, "Update").await?;
+        info!("Checking for updates");
+        self.config().channel_id.say(http, "Update").await?;
         Ok(())
     }
 
     /// Periodic update loop
+    ///
+    /// A reloaded `api_delay` applies from the latest update on.
     pub async fn update_loop(&self) -> Result<()> {
         let http =
             &Http::new_with_token_application_id(&self.token, u64::from(self.application_id));
@@ -89,42 +297,469 @@ impl Updater {
         match get_local_data(STORED_DATA_FILE) {
             Ok(_) => {}
             Err(_) => {
-                let prev = get_aoc_data(&self.aoc_cookie)
+                let prev = get_aoc_data(&self.config().aoc_cookie)
                     .await
                     .expect("Could not get initial AocData");
                 prev.write_to_file(STORED_DATA_FILE)
                     .expect("Could not write initial data to file");
             }
         };
-        let mut interval = interval(self.api_delay);
+        let mut config = self.config.clone();
+        let mut api_delay = config.borrow_and_update().api_delay;
+        let mut interval = interval(api_delay);
+        let mut last_update = Instant::now();
         loop {
-            interval.tick().await;
-            match self.update(http).await {
-                Ok(_) => {}
-                Err(err) => println!("{}", err),
+            tokio::select! {
+                _ = interval.tick() => {
+                    last_update = Instant::now();
+                    // A failed update is logged in its span and retried at the next tick.
+                    let _ = self.update(http).await;
+                }
+                Ok(()) = config.changed() => {
+                    let reloaded = config.borrow_and_update().api_delay;
+                    if reloaded != api_delay {
+                        info!(api_delay_s = reloaded.as_secs(), "Changed the update interval");
+                        api_delay = reloaded;
+                        interval = interval_at(last_update + api_delay, api_delay);
+                    }
+                }
             }
         }
     }
 
     /// Check for and publish update
+    ///
+    /// New stars for a day with a discussion thread are published in that thread.
+    /// The main channel gets the remaining stars, overtakes, new players and the current standings.
+    #[instrument(err, skip_all, fields(leaderboard = field::Empty))]
     async fn update(&self, http: &Http) -> Result<()> {
-        println!("Checking for updates");
+        debug!("Checking for updates");
+        let config = self.config();
+        let scoring = config.scoring();
         let prev = get_local_data("latest.json")?;
-        let latest_data = get_aoc_data(&self.aoc_cookie).await?;
-        let diff = latest_data.diff(&prev);
-        match diff {
-            Some(diff) => {
-                self.channel_id.say(http, &diff.fmt()).await?;
-                latest_data.write_to_file("latest.json")?;
-                Ok(())
+        Span::current().record("leaderboard", field::display(prev.leaderboard_id()));
+        let latest_data = async {
+            let fetch_start = Instant::now();
+            let fetched = get_aoc_data(&config.aoc_cookie).await;
+            self.metrics.fetch(&fetched, fetch_start.elapsed());
+            let latest_data = fetched?;
+            info!(
+                players = latest_data.num_players(),
+                elapsed_ms = elapsed_ms(fetch_start),
+                "Fetched leaderboard"
+            );
+            Ok::<_, AocError>(latest_data)
+        }
+        .instrument(info_span!("fetch"))
+        .await?;
+        let diff = info_span!("diff").in_scope(|| {
+            let diff = latest_data.diff_with(&prev, &scoring)?;
+            info!(
+                players_with_new_stars = diff.new_stars().count(),
+                new_players = diff.new_players().count(),
+                overtakes = diff.overtakes().count(),
+                "Found news"
+            );
+            for mismatch in latest_data.verify_scores(&scoring.excluded).mismatches {
+                warn!(%mismatch, "Local score mismatch");
+            }
+            Some(diff)
+        });
+        let diff = match diff {
+            Some(diff) => diff,
+            None => {
+                debug!("No news");
+                return Ok(());
+            }
+        };
+        self.publish_news(http, &config, &latest_data, &diff)
+            .await?;
+        info_span!("persist").in_scope(|| {
+            latest_data.write_to_file("latest.json")?;
+            debug!("Stored the latest data");
+            Ok(())
+        })
+    }
+
+    /// Publish `diff` in the day threads and the main channel, with the standings of `latest_data`
+    #[instrument(name = "notify", skip_all)]
+    async fn publish_news(
+        &self,
+        http: &Http,
+        config: &AocBotConfig,
+        latest_data: &AocData,
+        diff: &Diff,
+    ) -> Result<()> {
+        let scoring = config.scoring();
+        let locale = config.locale.get(config.channel_id);
+        let time_display = config.time_display.get(config.channel_id);
+        let format = config.format.get(config.channel_id);
+        let notify_start = Instant::now();
+        let threads = get_day_threads();
+        let mut main_msg = String::new();
+        for day in diff.days() {
+            let day_msg = discord_table(&diff.day_table(day, locale, time_display), format);
+            match threads.get(&day) {
+                Some(thread) => {
+                    thread.say(http, &day_msg).await?;
+                    self.metrics.message_sent();
+                }
+                None => main_msg.push_str(&day_msg),
+            }
+        }
+        for table in [diff.ranks_table(locale), diff.new_players_table(locale)] {
+            if !table.rows.is_empty() {
+                main_msg.push_str(&discord_table(&table, format));
             }
-            None => Ok(()),
         }
+        main_msg.push_str(&standings(
+            latest_data,
+            &scoring,
+            config.time_zone_fair().as_ref(),
+            locale,
+            format,
+        ));
+        self.notify(http, &main_msg).await?;
+        info!(elapsed_ms = elapsed_ms(notify_start), "Published news");
+        Ok(())
+    }
+
+    /// Publish a message in the main channel
+    async fn notify(&self, http: &Http, msg: &str) -> Result<()> {
+        self.config().channel_id.say(http, msg).await?;
+        self.metrics.message_sent();
+        Ok(())
+    }
+
+    /// Daily digest loop
+    ///
+    /// Publishes a digest at the configured local time every day.
+    /// Waits for a reloaded config while no digest time is configured.
+    pub async fn digest_loop(&self) -> Result<()> {
+        let http =
+            &Http::new_with_token_application_id(&self.token, u64::from(self.application_id));
+        let mut config = self.config.clone();
+        loop {
+            let digest_time = config.borrow_and_update().digest_time;
+            let digest_time = match digest_time {
+                Some(digest_time) => digest_time,
+                None => match config.changed().await {
+                    Ok(()) => continue,
+                    Err(_) => return Ok(()),
+                },
+            };
+            let now = Local::now();
+            let mut next = now
+                .date_naive()
+                .and_time(digest_time)
+                .and_local_timezone(Local)
+                .earliest()
+                .unwrap_or(now);
+            if next <= now {
+                next += ChronoDuration::days(1);
+            }
+            tokio::select! {
+                _ = sleep((next - now).to_std().unwrap_or_default()) => {
+                    match self.digest(http).await {
+                        Ok(_) => {}
+                        Err(err) => error!(error = %err, "Digest failed"),
+                    }
+                }
+                // Start over with the reloaded digest time.
+                Ok(()) = config.changed() => {}
+            }
+        }
+    }
+
+    /// Publish a digest of the changes since the previous digest
+    ///
+    /// The stored data is saved as the snapshot for the next digest.
+    #[instrument(skip_all, fields(leaderboard = field::Empty))]
+    async fn digest(&self, http: &Http) -> Result<()> {
+        let config = self.config();
+        let scoring = config.scoring();
+        let locale = config.locale.get(config.channel_id);
+        let latest = get_local_data(STORED_DATA_FILE)?;
+        let prev = get_local_data(DIGEST_DATA_FILE).unwrap_or_else(|_| latest.clone());
+        Span::current().record("leaderboard", field::display(latest.leaderboard_id()));
+        if let Some(day) = Day::active(latest.year()?, Utc::now()) {
+            let digest = Digest::new(&latest, &prev, day, config.digest_top, &scoring);
+            let time_display = config.time_display.get(config.channel_id);
+            self.notify(http, &digest.fmt(locale, time_display)).await?;
+            #[cfg(feature = "chart")]
+            {
+                Self::publish_chart(
+                    &config.channel_id,
+                    &self.metrics,
+                    http,
+                    &scoring,
+                    Metric::Points,
+                    locale,
+                )
+                .await?;
+            }
+            info!(%day, "Published digest");
+        }
+        latest.write_to_file(DIGEST_DATA_FILE)?;
+        Ok(())
+    }
+
+    /// Periodic puzzle unlock loop
+    ///
+    /// Creates a discussion thread for each day when its puzzle unlocks.
+    /// If the bot is started while a puzzle is active, a missing thread for that day is created
+    /// immediately.
+    pub async fn unlock_loop(&self) -> Result<()> {
+        let http =
+            &Http::new_with_token_application_id(&self.token, u64::from(self.application_id));
+        let year = self.event_year().await?;
+        if let Some(day) = Day::active(year, Utc::now()) {
+            if let Err(err) = self.create_day_thread(http, day).await {
+                error!(error = %err, %day, "Creating the day thread failed")
+            }
+        }
+        while let Some((day, wait)) = Day::next_unlock(year, Utc::now()) {
+            sleep(wait.to_std().unwrap_or_default()).await;
+            if let Err(err) = self.create_day_thread(http, day).await {
+                error!(error = %err, %day, "Creating the day thread failed")
+            }
+        }
+        Ok(())
+    }
+
+    /// Year of the tracked event
+    ///
+    /// Read from the stored data if present, otherwise from the API.
+    async fn event_year(&self) -> Result<i32> {
+        let data = match get_local_data(STORED_DATA_FILE) {
+            Ok(data) => data,
+            Err(_) => get_aoc_data(&self.config().aoc_cookie).await?,
+        };
+        Ok(data.year()?)
+    }
+
+    /// Announce an unlocked puzzle and start a discussion thread for it
+    async fn create_day_thread(&self, http: &Http, day: Day) -> Result<()> {
+        let mut threads = get_day_threads();
+        if threads.contains_key(&day) {
+            return Ok(());
+        }
+        let config = self.config();
+        let locale = config.locale.get(config.channel_id);
+        let msg = config
+            .channel_id
+            .say(
+                http,
+                format!(
+                    "{} {}",
+                    fill(locale.catalog().unlocked, &[&day]),
+                    STAR_SYMBOL
+                ),
+            )
+            .await?;
+        self.metrics.message_sent();
+        let thread = config
+            .channel_id
+            .create_public_thread(http, msg.id, |thread| {
+                thread.name(thread_name(day, None, locale))
+            })
+            .await?;
+        info!(%day, thread = %thread.id, "Created the day thread");
+        threads.insert(day, thread.id);
+        write_day_threads(&threads)
+    }
+
+    /// Add a puzzle title to the name of a day's discussion thread
+    ///
+    /// Titles are only added on request, to keep the thread names spoiler-safe.
+    /// Does nothing if `channel_id` is not a day thread.
+    async fn set_thread_title(
+        channel_id: &ChannelId,
+        ctx: &Context,
+        title: &str,
+        locale: Locale,
+    ) -> Result<()> {
+        let day = get_day_threads()
+            .into_iter()
+            .find(|(_, thread)| thread == channel_id)
+            .map(|(day, _)| day);
+        if let Some(day) = day {
+            channel_id
+                .edit(&ctx.http, |thread| {
+                    thread.name(thread_name(day, Some(title), locale))
+                })
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Milliseconds since `start`
+fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
+
+/// Standings according to `scoring`, followed by the time zone fair ranking if configured.
+fn standings(
+    data: &AocData,
+    scoring: &Excluding<Scoring>,
+    time_zone_fair: Option<&Excluding<TimeZoneFair>>,
+    locale: Locale,
+    format: Format,
+) -> String {
+    let mut standings = discord_table(&data.scores_table(scoring, locale), format);
+    if let Some(time_zone_fair) = time_zone_fair {
+        let mut table = data.scores_table(time_zone_fair, locale);
+        table.title = format!("{}: {}", locale.catalog().time_zone_fair, table.title);
+        standings.push_str(&discord_table(&table, format));
+    }
+    standings
+}
+
+/// `table` rendered in `format`, in a code block to keep it aligned unless it is Markdown
+///
+/// Markdown tables end with an empty line, so that a following table starts a new one.
+fn discord_table(table: &Table, format: Format) -> String {
+    match format {
+        Format::Markdown => format!("{}\n", format.render(table)),
+        _ => code_block(&format.render(table)),
     }
 }
 
+/// Spoiler-safe thread name, with the puzzle title only if one is given.
+fn thread_name(day: Day, title: Option<&str>, locale: Locale) -> String {
+    let label = locale.catalog().day;
+    match title {
+        Some(title) => format!("{} {}: {}", label, day, title),
+        None => format!("{} {}", label, day),
+    }
+}
+
+/// Get the stored day threads, empty if none are stored.
+fn get_day_threads() -> DayThreads {
+    File::open(THREADS_FILE)
+        .ok()
+        .and_then(|file| serde_json::from_reader(file).ok())
+        .unwrap_or_default()
+}
+
+fn write_day_threads(threads: &DayThreads) -> Result<()> {
+    serde_json::to_writer_pretty(&File::create(THREADS_FILE)?, threads)?;
+    Ok(())
+}
+
+/// Command in a message mentioning the bot
+#[derive(Debug, PartialEq)]
+enum Command {
+    /// Set the title of a day thread, e.g. "@aoc-bot title Sonar Sweep"
+    Title(String),
+    /// Statistics for a player, e.g. "@aoc-bot stats Jacko"
+    Stats(String),
+    /// Hardest days so far, e.g. "@aoc-bot hardest"
+    Hardest,
+    /// Head-to-head comparison, e.g. "@aoc-bot versus Jacko Nes", "@aoc-bot versus A B vs C"
+    /// or "@aoc-bot versus @Jacko @Nes"
+    Versus(String, String),
+    /// Progression chart of the points, or the ranks with "@aoc-bot chart rank"
+    Chart(Metric),
+    /// Solve time heatmap, e.g. "@aoc-bot heatmap"
+    Heatmap,
+    /// Number of solvers for each day, e.g. "@aoc-bot histogram"
+    Histogram,
+    /// Current standings, the default for any other message.
+    Score,
+}
+
+impl Command {
+    fn parse(content: &str) -> Self {
+        let mut words = content
+            .split_whitespace()
+            .skip_while(|word| word.starts_with("<@"));
+        let cmd = words.next();
+        let arg = words.collect::<Vec<&str>>().join(" ");
+        match cmd {
+            Some(TITLE_CMD) if !arg.is_empty() => Command::Title(arg),
+            Some(STATS_CMD) if !arg.is_empty() => Command::Stats(arg),
+            Some(HARDEST_CMD) => Command::Hardest,
+            Some(CHART_CMD) if arg == "rank" => Command::Chart(Metric::Rank),
+            Some(CHART_CMD) => Command::Chart(Metric::Points),
+            Some(HEATMAP_CMD) => Command::Heatmap,
+            Some(HISTOGRAM_CMD) => Command::Histogram,
+            Some(VERSUS_CMD) => match versus_names(&arg) {
+                Some((a, b)) => Command::Versus(a, b),
+                None => Command::Score,
+            },
+            _ => Command::Score,
+        }
+    }
+}
+
+/// Wrap `text` in a code block, to keep text renderings aligned
+fn code_block(text: &str) -> String {
+    format!("```\n{}```", text)
+}
+
+/// Split the argument of a versus command into two player names
+///
+/// Names with spaces are separated by " vs ", otherwise the names are single words.
+fn versus_names(arg: &str) -> Option<(String, String)> {
+    let names: Vec<&str> = if arg.contains(" vs ") {
+        arg.split(" vs ").map(str::trim).collect()
+    } else {
+        arg.split_whitespace().collect()
+    };
+    match names.as_slice() {
+        [a, b] if !a.is_empty() && !b.is_empty() => Some((a.to_string(), b.to_string())),
+        _ => None,
+    }
+}
+
+/// Discord user id of a mention, e.g. "<@123>" or "<@!123>"
+fn mention_id(word: &str) -> Option<u64> {
+    let id = word.strip_prefix("<@")?.strip_suffix('>')?;
+    id.trim_start_matches('!').parse().ok()
+}
+
+/// AoC player name of a versus argument
+///
+/// A mentioned user is looked up in `aoc_names`, falling back to their Discord name.
+/// Any other argument is taken as the name of the player.
+fn player_name(arg: &str, config: &AocBotConfig, mentions: &[User]) -> String {
+    let id = match mention_id(arg) {
+        Some(id) => id,
+        None => return arg.to_string(),
+    };
+    config
+        .aoc_names
+        .get(&id)
+        .cloned()
+        .or_else(|| {
+            mentions
+                .iter()
+                .find(|user| user.id.0 == id)
+                .map(|user| user.name.clone())
+        })
+        .unwrap_or_else(|| arg.to_string())
+}
+
 /// Bot sub-part actively listening to a channel
-struct Responder;
+struct Responder {
+    /// Running config, read for each message
+    config: LiveConfig,
+    metrics: Arc<Metrics>,
+}
+
+/// Channel with the settings of `channel_id`, the main channel for day threads
+fn settings_channel(config: &AocBotConfig, channel_id: ChannelId) -> ChannelId {
+    if get_day_threads()
+        .values()
+        .any(|thread| *thread == channel_id)
+    {
+        config.channel_id
+    } else {
+        channel_id
+    }
+}
 
 #[async_trait]
 impl EventHandler for Responder {
@@ -136,12 +771,76 @@ impl EventHandler for Responder {
         //println!("Got msg: '{:?}'", &msg);
 
         // Temp. hack: any time the bot is @:ed, then print score.
+        // Unless it is another command, see `Command`.
         if let Some(mnt) = msg.mentions.get(0) {
             // println!("{:?}", mnt);
             if mnt.name == "aoc-bot" {
-                match Updater::publish_score(&msg.channel_id, &ctx).await {
-                    Ok(_) => {}
-                    Err(err) => println!("{}", err),
+                let config = self.config.borrow().clone();
+                let scoring = config.scoring();
+                let settings = settings_channel(&config, msg.channel_id);
+                let locale = config.locale.get(settings);
+                let format = config.format.get(settings);
+                let command = Command::parse(&msg.content);
+                debug!(channel = %msg.channel_id, ?command, "Got a command");
+                let res = match command {
+                    Command::Title(title) => {
+                        Updater::set_thread_title(&msg.channel_id, &ctx, &title, locale).await
+                    }
+                    Command::Stats(name) => {
+                        Updater::publish_stats(&msg.channel_id, &self.metrics, &ctx, &name, locale)
+                            .await
+                            .map(|_| ())
+                    }
+                    Command::Hardest => {
+                        Updater::publish_hardest(&msg.channel_id, &self.metrics, &ctx, locale)
+                            .await
+                            .map(|_| ())
+                    }
+                    Command::Versus(a, b) => {
+                        let a = player_name(&a, &config, &msg.mentions);
+                        let b = player_name(&b, &config, &msg.mentions);
+                        Updater::publish_versus(
+                            &msg.channel_id,
+                            &self.metrics,
+                            &ctx,
+                            &a,
+                            &b,
+                            locale,
+                        )
+                        .await
+                        .map(|_| ())
+                    }
+                    Command::Chart(metric) => {
+                        Updater::publish_chart(
+                            &msg.channel_id,
+                            &self.metrics,
+                            &ctx.http,
+                            &scoring,
+                            metric,
+                            locale,
+                        )
+                        .await
+                    }
+                    Command::Heatmap => {
+                        Updater::publish_heatmap(&msg.channel_id, &self.metrics, &ctx.http).await
+                    }
+                    Command::Histogram => {
+                        Updater::publish_histogram(&msg.channel_id, &self.metrics, &ctx.http).await
+                    }
+                    Command::Score => Updater::publish_score(
+                        &msg.channel_id,
+                        &self.metrics,
+                        &ctx,
+                        &scoring,
+                        config.time_zone_fair().as_ref(),
+                        locale,
+                        format,
+                    )
+                    .await
+                    .map(|_| ()),
+                };
+                if let Err(err) = res {
+                    error!(error = %err, channel = %msg.channel_id, "Command failed");
                 }
             }
         }
@@ -159,6 +858,6 @@ impl EventHandler for Responder {
         // }
     }
     async fn ready(&self, _ctx: Context, ready: Ready) {
-        println!("{} is connected! {}", ready.user.name, STAR_SYMBOL);
+        info!(user = %ready.user.name, "Connected! {}", STAR_SYMBOL);
     }
 }
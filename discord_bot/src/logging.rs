@@ -0,0 +1,30 @@
+//! Diagnostics of the running bot, as text or JSON lines
+//!
+//! Levels are filtered with `RUST_LOG`, e.g. `RUST_LOG=aoc_discord_bot=debug`, and default to
+//! `info`.
+use clap::ValueEnum;
+use tracing_subscriber::EnvFilter;
+
+/// Default filter, when `RUST_LOG` is not set
+const DEFAULT_FILTER: &str = "info";
+
+/// Output format of the log
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable text, one event per line
+    #[default]
+    Pretty,
+    /// One JSON object per event, with the fields of the event and its spans
+    Json,
+}
+
+/// Install the global subscriber, writing events to stdout in `format`
+pub fn init(format: LogFormat) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match format {
+        LogFormat::Pretty => subscriber.init(),
+        LogFormat::Json => subscriber.json().flatten_event(true).init(),
+    }
+}
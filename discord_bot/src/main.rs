@@ -1,12 +1,87 @@
-use aoc_discord_bot::{config::AocBotConfig, try_responder_client_and_updater_from_config};
+use aoc_discord_bot::{
+    channel_problems,
+    config::{AocBotConfig, FieldOverride},
+    logging::{self, LogFormat},
+    metrics::{serve, Metrics},
+    reload::reload_loop,
+    try_responder_client_and_updater_from_config,
+};
+use clap::Parser;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::error;
+
+/// Discord bot for the annual Advent of Code competition
+#[derive(Debug, Parser)]
+struct Args {
+    /// Config file, JSON or TOML, overridden field by field by `AOC_BOT_*` environment variables
+    ///
+    /// Reloaded while running when the file changes or on SIGHUP.
+    #[arg(short, long, env = "AOC_BOT_CONFIG", default_value = "config.json")]
+    config: String,
+    /// Override a config field with `field=value`, e.g. `--set digest_top=5`. Can be repeated.
+    ///
+    /// Takes precedence over the environment and the config file, including on reload.
+    /// Values are given as in the `AOC_BOT_*` environment variables.
+    #[arg(long = "set", value_name = "FIELD=VALUE")]
+    set: Vec<FieldOverride>,
+    /// Validate the config, including access to the channels, and exit without starting the bot
+    ///
+    /// Lists all problems with the values, but only the first error if the config cannot be parsed.
+    #[arg(long)]
+    check_config: bool,
+    /// Log output, filtered by `RUST_LOG`
+    #[arg(long, value_enum, env = "AOC_BOT_LOG_FORMAT", default_value = "pretty")]
+    log_format: LogFormat,
+    /// Address to serve Prometheus metrics on `/metrics` and a health check on `/healthz`,
+    /// e.g. `127.0.0.1:9187`. Not served if not given.
+    #[arg(long, env = "AOC_BOT_METRICS_ADDR")]
+    metrics_addr: Option<SocketAddr>,
+}
 
 #[tokio::main]
 async fn main() {
-    let config = AocBotConfig::from_config("config.json").expect("Parsing `config.json` failed.");
+    let args = Args::parse();
+    logging::init(args.log_format);
+    let config = match AocBotConfig::from_config(&args.config, &args.set) {
+        Ok(config) => config,
+        Err(err) => {
+            error!(error = format!("{:#}", err), "Invalid config");
+            std::process::exit(1);
+        }
+    };
+    let mut problems = config.problems();
+    if args.check_config {
+        problems.extend(channel_problems(&config).await);
+    }
+    for problem in &problems {
+        error!(error = %problem, "Invalid config");
+    }
+    if !problems.is_empty() {
+        std::process::exit(1);
+    }
+    if args.check_config {
+        println!("Config OK");
+        return;
+    }
 
-    let (mut responder, updater) = try_responder_client_and_updater_from_config(config)
-        .await
-        .expect("Create bot failed.");
+    let (reload_sender, live_config) = watch::channel(Arc::new(config));
+    let metrics = Arc::new(Metrics::default());
+    let metrics_server = {
+        let (addr, metrics, config) = (args.metrics_addr, metrics.clone(), live_config.clone());
+        async move {
+            if let Some(addr) = addr {
+                if let Err(err) = serve(addr, metrics, config).await {
+                    error!(error = %err, %addr, "Serving metrics failed");
+                }
+            }
+        }
+    };
+    let (mut responder, updater) =
+        try_responder_client_and_updater_from_config(live_config, metrics)
+            .await
+            .expect("Create bot failed.");
 
     // Old version with select, I can't recall why I chose select here, join seems more
     // appropriate.
@@ -18,5 +93,12 @@ async fn main() {
     //         println!("The responder stopped unexpectedly")
     //     }
     // };
-    tokio::join!(updater.update_loop(), responder.start());
+    tokio::join!(
+        updater.update_loop(),
+        updater.unlock_loop(),
+        updater.digest_loop(),
+        reload_loop(args.config, args.set, reload_sender),
+        metrics_server,
+        responder.start()
+    );
 }
@@ -0,0 +1,317 @@
+//! Prometheus metrics and a health check, served over HTTP
+//!
+//! Only served if an address is given, see `--metrics-addr`.
+//! `/metrics` has the metrics in the Prometheus text format and `/healthz` is unhealthy if
+//! no fetch has succeeded within [`HEALTHY_POLLS`] poll intervals.
+use crate::config::LiveConfig;
+use anyhow::Result;
+use aoc_data::{AocData, AocError};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info};
+
+/// Number of poll intervals without a successful fetch before the bot is unhealthy
+pub const HEALTHY_POLLS: u32 = 3;
+/// Upper bounds of the fetch latency buckets, in seconds
+const LATENCY_BUCKETS: [f64; 8] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+/// Longest request read, the bot only serves short GET requests
+const MAX_REQUEST: usize = 4096;
+/// Content type of the Prometheus text format
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+/// Content type of all other responses
+const TEXT_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+/// Metrics of the running bot, shared by the updater, the responder and the server
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    last_fetch: Mutex<Option<Instant>>,
+    /// Number of fetches with a latency at most each of [`LATENCY_BUCKETS`]
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    latency_micros: AtomicU64,
+    fetches: AtomicU64,
+    successes: AtomicU64,
+    /// Failed fetches by [`AocError::kind`]
+    failures: Mutex<BTreeMap<&'static str, u64>>,
+    messages: AtomicU64,
+    players: AtomicU64,
+    stars: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            last_fetch: Mutex::new(None),
+            latency_buckets: Default::default(),
+            latency_micros: AtomicU64::new(0),
+            fetches: AtomicU64::new(0),
+            successes: AtomicU64::new(0),
+            failures: Mutex::new(BTreeMap::new()),
+            messages: AtomicU64::new(0),
+            players: AtomicU64::new(0),
+            stars: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Metrics {
+    /// Record a fetch from the AoC API, which took `latency`
+    pub fn fetch(&self, result: &Result<AocData, AocError>, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+            if secs <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.latency_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+        self.fetches.fetch_add(1, Ordering::Relaxed);
+        match result {
+            Ok(data) => {
+                self.successes.fetch_add(1, Ordering::Relaxed);
+                *lock(&self.last_fetch) = Some(Instant::now());
+                self.players
+                    .store(data.num_players() as u64, Ordering::Relaxed);
+                self.stars
+                    .store(u64::from(data.num_stars()), Ordering::Relaxed);
+            }
+            Err(err) => *lock(&self.failures).entry(err.kind()).or_default() += 1,
+        }
+    }
+
+    /// Record a message sent to Discord
+    pub fn message_sent(&self) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether a fetch has succeeded within [`HEALTHY_POLLS`] times `api_delay` before `now`
+    ///
+    /// Counts from the start of the bot until the first successful fetch.
+    pub fn healthy(&self, api_delay: Duration, now: Instant) -> bool {
+        let since = lock(&self.last_fetch).unwrap_or(self.started);
+        now.saturating_duration_since(since) <= api_delay * HEALTHY_POLLS
+    }
+
+    /// All metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let fetches = self.fetches.load(Ordering::Relaxed);
+        let failures = lock(&self.failures).clone();
+
+        let latency = "aoc_bot_fetch_duration_seconds";
+        header(
+            &mut text,
+            latency,
+            "histogram",
+            "Latency of fetches from the AoC API",
+        );
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+            let count = bucket.load(Ordering::Relaxed);
+            let _ = writeln!(text, "{}_bucket{{le=\"{}\"}} {}", latency, bound, count);
+        }
+        let _ = writeln!(text, "{}_bucket{{le=\"+Inf\"}} {}", latency, fetches);
+        let secs = self.latency_micros.load(Ordering::Relaxed) as f64 / 1e6;
+        let _ = writeln!(text, "{}_sum {}", latency, secs);
+        let _ = writeln!(text, "{}_count {}", latency, fetches);
+
+        let fetches_total = "aoc_bot_fetches_total";
+        let help = "Fetches from the AoC API, by result";
+        header(&mut text, fetches_total, "counter", help);
+        let successes = self.successes.load(Ordering::Relaxed);
+        let failed: u64 = failures.values().sum();
+        for (result, count) in [("ok", successes), ("error", failed)] {
+            let _ = writeln!(text, "{}{{result=\"{}\"}} {}", fetches_total, result, count);
+        }
+
+        let failures_total = "aoc_bot_fetch_failures_total";
+        let help = "Failed fetches from the AoC API, by kind of error";
+        header(&mut text, failures_total, "counter", help);
+        for (kind, count) in failures {
+            let _ = writeln!(text, "{}{{kind=\"{}\"}} {}", failures_total, kind, count);
+        }
+
+        if let Some(last_fetch) = *lock(&self.last_fetch) {
+            let help = "Time since the last successful fetch";
+            let secs = last_fetch.elapsed().as_secs();
+            single(
+                &mut text,
+                "aoc_bot_seconds_since_last_fetch",
+                "gauge",
+                help,
+                secs,
+            );
+        }
+
+        let messages = self.messages.load(Ordering::Relaxed);
+        let help = "Messages sent to Discord";
+        single(
+            &mut text,
+            "aoc_bot_messages_sent_total",
+            "counter",
+            help,
+            messages,
+        );
+
+        let players = self.players.load(Ordering::Relaxed);
+        single(
+            &mut text,
+            "aoc_bot_players",
+            "gauge",
+            "Players on the leaderboard",
+            players,
+        );
+
+        let stars = self.stars.load(Ordering::Relaxed);
+        let help = "Stars of all players on the leaderboard";
+        single(&mut text, "aoc_bot_stars", "gauge", help, stars);
+        text
+    }
+}
+
+/// Help and type lines of a metric
+fn header(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} {}", name, kind);
+}
+
+/// Metric with a single value and no labels
+fn single(text: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    header(text, name, kind, help);
+    let _ = writeln!(text, "{} {}", name, value);
+}
+
+/// Lock `mutex`, also if a thread panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Serve `/metrics` and `/healthz` on `addr`
+///
+/// The health check follows the `api_delay` of the running `config`.
+pub async fn serve(addr: SocketAddr, metrics: Arc<Metrics>, config: LiveConfig) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(%addr, "Serving metrics");
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let metrics = metrics.clone();
+        let api_delay = config.borrow().api_delay;
+        tokio::spawn(async move {
+            if let Err(err) = respond(stream, &metrics, api_delay).await {
+                debug!(%peer, error = %err, "Metrics request failed");
+            }
+        });
+    }
+}
+
+/// Answer a single HTTP request, and close the connection
+async fn respond(mut stream: TcpStream, metrics: &Metrics, api_delay: Duration) -> Result<()> {
+    let mut request = vec![0; MAX_REQUEST];
+    let len = stream.read(&mut request).await?;
+    let request = String::from_utf8_lossy(&request[..len]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+    let (status, content_type, body) = route(path, metrics, api_delay, Instant::now());
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Status line, content type and body for a request of `path`
+fn route(
+    path: &str,
+    metrics: &Metrics,
+    api_delay: Duration,
+    now: Instant,
+) -> (&'static str, &'static str, String) {
+    match path {
+        "/metrics" => ("200 OK", METRICS_CONTENT_TYPE, metrics.render()),
+        "/healthz" if metrics.healthy(api_delay, now) => {
+            ("200 OK", TEXT_CONTENT_TYPE, "ok\n".to_string())
+        }
+        "/healthz" => (
+            "503 Service Unavailable",
+            TEXT_CONTENT_TYPE,
+            format!("no successful fetch in the last {} polls\n", HEALTHY_POLLS),
+        ),
+        _ => (
+            "404 Not Found",
+            TEXT_CONTENT_TYPE,
+            "not found\n".to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoc_data::get_local_data;
+
+    fn failure() -> Result<AocData, AocError> {
+        Err(AocError::Param {
+            param: "event".to_string(),
+            val: String::new(),
+            reason: String::new(),
+        })
+    }
+
+    #[test]
+    fn render_metrics() {
+        let metrics = Metrics::default();
+        metrics.fetch(&failure(), Duration::from_millis(300));
+        metrics.message_sent();
+        let text = metrics.render();
+        assert!(text.contains("aoc_bot_fetch_duration_seconds_bucket{le=\"0.25\"} 0\n"));
+        assert!(text.contains("aoc_bot_fetch_duration_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(text.contains("aoc_bot_fetch_duration_seconds_count 1\n"));
+        assert!(text.contains("aoc_bot_fetches_total{result=\"ok\"} 0\n"));
+        assert!(text.contains("aoc_bot_fetches_total{result=\"error\"} 1\n"));
+        assert!(text.contains("aoc_bot_fetch_failures_total{kind=\"param\"} 1\n"));
+        assert!(text.contains("aoc_bot_messages_sent_total 1\n"));
+        assert!(!text.contains("aoc_bot_seconds_since_last_fetch"));
+    }
+
+    #[test]
+    fn health() {
+        let metrics = Metrics::default();
+        let api_delay = Duration::from_secs(60);
+        let later = |polls: u32| metrics.started + api_delay * polls;
+        let healthy = route("/healthz", &metrics, api_delay, later(1));
+        assert_eq!((healthy.0, healthy.1), ("200 OK", TEXT_CONTENT_TYPE));
+        let scrape = route("/metrics", &metrics, api_delay, later(1));
+        assert_eq!(scrape.1, METRICS_CONTENT_TYPE);
+        assert_eq!(
+            route("/healthz", &metrics, api_delay, later(HEALTHY_POLLS + 1)).0,
+            "503 Service Unavailable"
+        );
+        metrics.fetch(&failure(), Duration::from_secs(1));
+        assert!(!metrics.healthy(api_delay, later(HEALTHY_POLLS + 1)));
+        let data = get_local_data("../data/tests/data/time_1.json");
+        metrics.fetch(&data, Duration::from_secs(1));
+        assert!(metrics.healthy(api_delay, Instant::now()));
+        let players = format!(
+            "aoc_bot_players {}\n",
+            data.expect("Test data").num_players()
+        );
+        assert!(metrics.render().contains(&players));
+        assert!(metrics
+            .render()
+            .contains("aoc_bot_fetches_total{result=\"ok\"} 1\n"));
+        assert_eq!(
+            route("/other", &metrics, api_delay, later(0)).0,
+            "404 Not Found"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Reloading of the config while the bot is running
+//!
+//! The config file is checked for changes every few seconds, and reloaded on SIGHUP.
+//! An invalid config is reported and ignored, the bot keeps running with the previous one.
+use crate::config::{AocBotConfig, FieldOverride};
+use anyhow::Result;
+use std::fs;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::watch;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+
+/// Time between checks for changes of the config file
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reload the config file `path` into `sender` whenever it changes or the bot gets SIGHUP
+///
+/// The command line `overrides` apply to each reloaded config.
+pub async fn reload_loop(
+    path: String,
+    overrides: Vec<FieldOverride>,
+    sender: watch::Sender<Arc<AocBotConfig>>,
+) -> Result<()> {
+    let mut hangup = Hangup::new()?;
+    let mut interval = interval(WATCH_INTERVAL);
+    let mut last_modified = modified(&path);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let latest = modified(&path);
+                if latest == last_modified {
+                    continue;
+                }
+                last_modified = latest;
+                info!(%path, "Config changed, reloading");
+            }
+            _ = hangup.recv() => info!(%path, "Got SIGHUP, reloading config"),
+        }
+        reload(&path, &overrides, &sender);
+    }
+}
+
+/// Replace the running config with the config file `path` and `overrides`, if it is valid
+fn reload(path: &str, overrides: &[FieldOverride], sender: &watch::Sender<Arc<AocBotConfig>>) {
+    let new = match AocBotConfig::from_config(path, overrides) {
+        Ok(new) => new,
+        Err(err) => {
+            error!(error = format!("{:#}", err), "Config not reloaded");
+            return;
+        }
+    };
+    let problems = new.problems();
+    if !problems.is_empty() {
+        for problem in problems {
+            error!(error = %problem, "Config not reloaded");
+        }
+        return;
+    }
+    let (new, kept) = sender.borrow().reload(new);
+    for field in kept {
+        warn!(
+            field,
+            "Field cannot change while the bot is running, restart to apply it"
+        );
+    }
+    sender.send_replace(Arc::new(new));
+    info!("Config reloaded");
+}
+
+/// Last modification of the file `path`, `None` if it cannot be read
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// SIGHUP, which never arrives on platforms without it
+struct Hangup {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+}
+
+impl Hangup {
+    #[cfg(unix)]
+    fn new() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            signal: signal(SignalKind::hangup())?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+
+    #[cfg(unix)]
+    async fn recv(&mut self) {
+        self.signal.recv().await;
+    }
+
+    #[cfg(not(unix))]
+    async fn recv(&mut self) {
+        std::future::pending().await
+    }
+}